@@ -0,0 +1,18 @@
+-- Item categories from SDE categoryIDs.yaml (e.g. Ship, Module, Charge, Blueprint)
+CREATE TABLE eve_categories (
+    category_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    published BOOLEAN NOT NULL DEFAULT 0
+);
+
+-- Item groups from SDE groupIDs.yaml (e.g. Rig Armor, Frigate, Salvaged Materials)
+CREATE TABLE eve_groups (
+    group_id INTEGER PRIMARY KEY,
+    category_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    published BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (category_id) REFERENCES eve_categories(category_id)
+);
+
+CREATE INDEX idx_eve_groups_category ON eve_groups(category_id);
+CREATE INDEX idx_eve_types_category ON eve_types(category_id);
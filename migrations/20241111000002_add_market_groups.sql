@@ -0,0 +1,11 @@
+-- Market group hierarchy from SDE marketGroups.yaml (the in-game market browser tree)
+CREATE TABLE market_groups (
+    market_group_id INTEGER PRIMARY KEY,
+    parent_group_id INTEGER, -- NULL for top-level groups
+    name TEXT NOT NULL,
+    description TEXT,
+    has_types BOOLEAN NOT NULL DEFAULT 0 -- Leaf groups that directly contain types
+);
+
+CREATE INDEX idx_market_groups_parent ON market_groups(parent_group_id);
+CREATE INDEX idx_eve_types_market_group ON eve_types(market_group_id);
@@ -0,0 +1,17 @@
+-- SDE import runs, so an interrupted import can be resumed
+CREATE TABLE sde_import_runs (
+    run_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sde_source TEXT NOT NULL, -- Path of the SDE being imported
+    started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME -- NULL until every stage has committed
+);
+
+-- Stages committed within an import run (written in the same transaction as the stage's data)
+CREATE TABLE sde_import_stages (
+    run_id INTEGER NOT NULL,
+    stage TEXT NOT NULL,
+    rows_imported INTEGER NOT NULL DEFAULT 0,
+    completed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (run_id, stage),
+    FOREIGN KEY (run_id) REFERENCES sde_import_runs(run_id)
+);
@@ -0,0 +1,21 @@
+-- Track which rows came from an SDE import, so re-imports can detect removals
+ALTER TABLE eve_types ADD COLUMN from_sde BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE blueprints ADD COLUMN from_sde BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE material_reprocessing ADD COLUMN from_sde BOOLEAN NOT NULL DEFAULT 0;
+
+-- Checksum of the SDE files imported by each run
+ALTER TABLE sde_import_runs ADD COLUMN sde_checksum TEXT;
+
+-- Per-run change log: what each import added, changed or removed
+CREATE TABLE sde_import_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL,
+    -- What changed, named by the import stage that logs it (e.g. 'type', 'blueprint', 'reprocessing')
+    entity TEXT NOT NULL,
+    -- type_id of the changed type (blueprint_type_id for blueprint entities)
+    entity_id INTEGER NOT NULL,
+    change TEXT NOT NULL, -- 'added', 'changed' or 'removed'
+    FOREIGN KEY (run_id) REFERENCES sde_import_runs(run_id)
+);
+
+CREATE INDEX idx_sde_import_changes_run ON sde_import_changes(run_id);
@@ -0,0 +1,11 @@
+-- Localized type names and descriptions from SDE typeIDs.yaml (one row per language)
+CREATE TABLE eve_type_names (
+    type_id INTEGER NOT NULL,
+    language TEXT NOT NULL, -- SDE language code: 'en', 'de', 'ru', 'fr', 'ja', 'zh', ...
+    name TEXT NOT NULL,
+    description TEXT,
+    PRIMARY KEY (type_id, language),
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_eve_type_names_name ON eve_type_names(name);
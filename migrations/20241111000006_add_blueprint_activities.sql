@@ -0,0 +1,45 @@
+-- Blueprint activities other than manufacturing, from SDE blueprints.yaml
+-- activity is 'invention', 'copying', 'research_material' or 'research_time'
+CREATE TABLE blueprint_activities (
+    blueprint_type_id INTEGER NOT NULL,
+    activity TEXT NOT NULL,
+    time INTEGER, -- Base job time in seconds
+    PRIMARY KEY (blueprint_type_id, activity),
+    FOREIGN KEY (blueprint_type_id) REFERENCES eve_types(type_id)
+);
+
+-- Materials consumed per activity job (e.g. datacores for invention)
+CREATE TABLE blueprint_activity_materials (
+    blueprint_type_id INTEGER NOT NULL,
+    activity TEXT NOT NULL,
+    material_type_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL,
+    PRIMARY KEY (blueprint_type_id, activity, material_type_id),
+    FOREIGN KEY (blueprint_type_id) REFERENCES eve_types(type_id),
+    FOREIGN KEY (material_type_id) REFERENCES eve_types(type_id)
+);
+
+-- Activity outputs (e.g. the T2 blueprint copy produced by invention)
+CREATE TABLE blueprint_activity_products (
+    blueprint_type_id INTEGER NOT NULL,
+    activity TEXT NOT NULL,
+    product_type_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL,
+    probability REAL, -- Base success chance (invention); NULL when the output is guaranteed
+    PRIMARY KEY (blueprint_type_id, activity, product_type_id),
+    FOREIGN KEY (blueprint_type_id) REFERENCES eve_types(type_id),
+    FOREIGN KEY (product_type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_blueprint_activity_products_product ON blueprint_activity_products(product_type_id);
+
+-- Skills required to run an activity
+CREATE TABLE blueprint_activity_skills (
+    blueprint_type_id INTEGER NOT NULL,
+    activity TEXT NOT NULL,
+    skill_type_id INTEGER NOT NULL,
+    level INTEGER NOT NULL,
+    PRIMARY KEY (blueprint_type_id, activity, skill_type_id),
+    FOREIGN KEY (blueprint_type_id) REFERENCES eve_types(type_id),
+    FOREIGN KEY (skill_type_id) REFERENCES eve_types(type_id)
+);
@@ -0,0 +1,2 @@
+-- Units produced per manufacturing run (e.g. 100 for most ammunition)
+ALTER TABLE blueprints ADD COLUMN product_quantity INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,18 @@
+-- Skills required to run a manufacturing job, from SDE blueprints.yaml
+CREATE TABLE blueprint_skills (
+    blueprint_type_id INTEGER NOT NULL,
+    skill_type_id INTEGER NOT NULL,
+    level INTEGER NOT NULL,
+    PRIMARY KEY (blueprint_type_id, skill_type_id),
+    FOREIGN KEY (blueprint_type_id) REFERENCES blueprints(blueprint_type_id),
+    FOREIGN KEY (skill_type_id) REFERENCES eve_types(type_id)
+);
+
+-- Specific processing skill that improves reprocessing yield of a type
+-- (dogma attribute reprocessingSkillType from SDE typeDogma.yaml, e.g. Scrapmetal Processing)
+CREATE TABLE reprocessing_skills (
+    source_type_id INTEGER PRIMARY KEY,
+    skill_type_id INTEGER NOT NULL,
+    FOREIGN KEY (source_type_id) REFERENCES eve_types(type_id),
+    FOREIGN KEY (skill_type_id) REFERENCES eve_types(type_id)
+);
@@ -0,0 +1,52 @@
+-- Universe map from SDE fsd/universe (names from bsd/invNames.yaml)
+CREATE TABLE regions (
+    region_id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE constellations (
+    constellation_id INTEGER PRIMARY KEY,
+    region_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    FOREIGN KEY (region_id) REFERENCES regions(region_id)
+);
+
+CREATE INDEX idx_constellations_region ON constellations(region_id);
+
+CREATE TABLE solar_systems (
+    solar_system_id INTEGER PRIMARY KEY,
+    constellation_id INTEGER NOT NULL,
+    region_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    security REAL NOT NULL, -- True security status (-1.0 to 1.0)
+    FOREIGN KEY (constellation_id) REFERENCES constellations(constellation_id),
+    FOREIGN KEY (region_id) REFERENCES regions(region_id)
+);
+
+CREATE INDEX idx_solar_systems_region ON solar_systems(region_id);
+CREATE INDEX idx_solar_systems_name ON solar_systems(name);
+
+-- Stargates; each gate jumps to a destination gate in a neighbouring system
+CREATE TABLE stargates (
+    stargate_id INTEGER PRIMARY KEY,
+    solar_system_id INTEGER NOT NULL,
+    destination_stargate_id INTEGER NOT NULL,
+    destination_system_id INTEGER NOT NULL,
+    FOREIGN KEY (solar_system_id) REFERENCES solar_systems(solar_system_id),
+    FOREIGN KEY (destination_system_id) REFERENCES solar_systems(solar_system_id)
+);
+
+CREATE INDEX idx_stargates_system ON stargates(solar_system_id);
+
+-- NPC stations from SDE bsd/staStations.yaml
+CREATE TABLE stations (
+    station_id INTEGER PRIMARY KEY,
+    solar_system_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    station_type_id INTEGER,
+    reprocessing_efficiency REAL, -- Station base reprocessing yield (e.g. 0.5)
+    reprocessing_stations_take REAL, -- Fraction of reprocessed output kept by the station
+    FOREIGN KEY (solar_system_id) REFERENCES solar_systems(solar_system_id)
+);
+
+CREATE INDEX idx_stations_system ON stations(solar_system_id);
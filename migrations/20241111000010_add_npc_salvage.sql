@@ -0,0 +1,46 @@
+-- Pirate NPC ships, derived from SDE NPC groups (e.g. "Asteroid Serpentis Cruiser")
+CREATE TABLE npc_types (
+    type_id INTEGER PRIMARY KEY,
+    faction TEXT NOT NULL, -- e.g. 'serpentis'
+    ship_class TEXT NOT NULL, -- frigate, destroyer, cruiser, battlecruiser, battleship, hauler, officer
+    wreck_size TEXT NOT NULL CHECK (wreck_size IN ('small', 'medium', 'large')),
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_npc_types_faction ON npc_types(faction, wreck_size);
+
+-- Salvage a faction's wrecks can yield, per wreck size.
+-- The SDE has no loot tables, so these are community-sourced averages per salvaged wreck.
+CREATE TABLE wreck_salvage (
+    faction TEXT NOT NULL,
+    wreck_size TEXT NOT NULL CHECK (wreck_size IN ('small', 'medium', 'large')),
+    salvage_type_id INTEGER NOT NULL,
+    drop_chance REAL NOT NULL, -- Chance (0.0 - 1.0) the item drops from a salvaged wreck
+    avg_quantity REAL NOT NULL, -- Average stack size when it drops
+    PRIMARY KEY (faction, wreck_size, salvage_type_id),
+    FOREIGN KEY (salvage_type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_wreck_salvage_type ON wreck_salvage(salvage_type_id);
+
+-- Serpentis wrecks (armor-tanked Gallente hulls)
+INSERT INTO wreck_salvage (faction, wreck_size, salvage_type_id, drop_chance, avg_quantity) VALUES
+('serpentis', 'small', 25595, 0.25, 1.2),  -- Tripped Power Circuit
+('serpentis', 'small', 25596, 0.20, 1.1),  -- Charred Micro Circuit
+('serpentis', 'small', 25606, 0.15, 1.0),  -- Armor Plates
+('serpentis', 'small', 25605, 0.10, 1.0),  -- Burned Logic Circuit
+('serpentis', 'small', 25597, 0.08, 1.0),  -- Fried Interface Circuit
+('serpentis', 'medium', 25595, 0.30, 1.5),
+('serpentis', 'medium', 25596, 0.25, 1.4),
+('serpentis', 'medium', 25606, 0.25, 1.3),
+('serpentis', 'medium', 25605, 0.18, 1.2),
+('serpentis', 'medium', 25604, 0.15, 1.2), -- Contaminated Nanite Compound
+('serpentis', 'medium', 25597, 0.12, 1.1),
+('serpentis', 'large', 25595, 0.35, 2.0),
+('serpentis', 'large', 25596, 0.30, 1.8),
+('serpentis', 'large', 25606, 0.35, 1.8),
+('serpentis', 'large', 25605, 0.25, 1.5),
+('serpentis', 'large', 25604, 0.25, 1.5),
+('serpentis', 'large', 25597, 0.20, 1.4),
+('serpentis', 'large', 28668, 0.10, 1.2),  -- Contaminated Lorentz Fluid
+('serpentis', 'large', 30748, 0.05, 1.0);  -- Damaged Artificial Neural Network
@@ -0,0 +1,13 @@
+-- Selected dogma attributes per type, from SDE typeDogma.yaml
+CREATE TABLE type_attributes (
+    type_id INTEGER PRIMARY KEY,
+    meta_level INTEGER, -- metaLevelOld (0 = T1, 5 = T2, 6+ = faction/officer)
+    tech_level INTEGER, -- techLevel (1, 2 or 3)
+    rig_size INTEGER, -- rigSize (1 = small, 2 = medium, 3 = large, 4 = capital)
+    calibration INTEGER, -- upgradeCost: calibration a rig uses
+    slot TEXT, -- 'high', 'medium', 'low', 'rig' or 'subsystem', from the type's dogma effects
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_type_attributes_rig_size ON type_attributes(rig_size);
+CREATE INDEX idx_type_attributes_tech_level ON type_attributes(tech_level);
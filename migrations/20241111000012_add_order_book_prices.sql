@@ -0,0 +1,3 @@
+-- Best bid/ask from regional order books, alongside the percentile buy/sell prices
+ALTER TABLE market_prices ADD COLUMN best_bid REAL; -- Highest buy order
+ALTER TABLE market_prices ADD COLUMN best_ask REAL; -- Lowest sell order
@@ -0,0 +1,28 @@
+-- Key the price cache by type and market location, so several hubs can be cached at once.
+-- A location is either a whole region ('region', e.g. 10000002 The Forge)
+-- or a single station ('station', e.g. 60003760 Jita IV - Moon 4).
+CREATE TABLE market_prices_new (
+    type_id INTEGER NOT NULL,
+    location_type TEXT NOT NULL CHECK (location_type IN ('region', 'station')),
+    location_id INTEGER NOT NULL,
+    sell_price REAL,
+    buy_price REAL,
+    daily_volume INTEGER,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    best_bid REAL, -- Highest buy order
+    best_ask REAL, -- Lowest sell order
+    PRIMARY KEY (type_id, location_type, location_id),
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+-- Existing prices were all region-wide
+INSERT INTO market_prices_new
+    (type_id, location_type, location_id, sell_price, buy_price, daily_volume, updated_at, best_bid, best_ask)
+SELECT type_id, 'region', region_id, sell_price, buy_price, daily_volume, updated_at, best_bid, best_ask
+FROM market_prices;
+
+DROP TABLE market_prices;
+ALTER TABLE market_prices_new RENAME TO market_prices;
+
+CREATE INDEX idx_market_prices_updated ON market_prices(updated_at);
+CREATE INDEX idx_market_prices_location ON market_prices(location_type, location_id);
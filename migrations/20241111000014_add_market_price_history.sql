@@ -0,0 +1,23 @@
+-- Every price refresh, appended, so trends survive the market_prices cache being overwritten
+CREATE TABLE market_price_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    type_id INTEGER NOT NULL,
+    location_type TEXT NOT NULL CHECK (location_type IN ('region', 'station')),
+    location_id INTEGER NOT NULL,
+    sell_price REAL,
+    buy_price REAL,
+    best_bid REAL,
+    best_ask REAL,
+    daily_volume INTEGER,
+    recorded_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+CREATE INDEX idx_market_price_history_lookup
+    ON market_price_history(type_id, location_type, location_id, recorded_at);
+
+-- Start each series with the currently cached price
+INSERT INTO market_price_history
+    (type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask, daily_volume, recorded_at)
+SELECT type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask, daily_volume, updated_at
+FROM market_prices;
@@ -0,0 +1,12 @@
+-- Runs of the background price refresh (src/services/price_refresh.rs)
+CREATE TABLE price_refresh_runs (
+    run_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    finished_at DATETIME, -- NULL while running
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'success', 'failed')),
+    locations INTEGER NOT NULL DEFAULT 0, -- Regions and stations refreshed
+    updated_count INTEGER NOT NULL DEFAULT 0, -- Prices stored
+    expires_at DATETIME, -- When ESI's cached order books expire (from its Expires header)
+    next_run_at DATETIME, -- When the next refresh is due
+    error TEXT
+);
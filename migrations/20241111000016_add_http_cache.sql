@@ -0,0 +1,9 @@
+-- Cached responses of external API GETs, revalidated with If-None-Match (src/external/http_cache.rs)
+CREATE TABLE http_cache (
+    url TEXT PRIMARY KEY,
+    etag TEXT,
+    expires_at DATETIME, -- From the Expires header; the cached body is used without a request until then
+    headers TEXT NOT NULL, -- Response headers as a JSON object
+    body BLOB NOT NULL,
+    fetched_at DATETIME DEFAULT CURRENT_TIMESTAMP -- Last 200 or 304 response
+);
@@ -0,0 +1,51 @@
+-- Allow a 'global' location (location_id 0) for prices that are the same everywhere,
+-- such as ESI's average prices, so they are no longer stored as if they were The Forge's.
+CREATE TABLE market_prices_new (
+    type_id INTEGER NOT NULL,
+    location_type TEXT NOT NULL CHECK (location_type IN ('region', 'station', 'global')),
+    location_id INTEGER NOT NULL,
+    sell_price REAL,
+    buy_price REAL,
+    daily_volume INTEGER,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    best_bid REAL, -- Highest buy order
+    best_ask REAL, -- Lowest sell order
+    PRIMARY KEY (type_id, location_type, location_id),
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+INSERT INTO market_prices_new
+    (type_id, location_type, location_id, sell_price, buy_price, daily_volume, updated_at, best_bid, best_ask)
+SELECT type_id, location_type, location_id, sell_price, buy_price, daily_volume, updated_at, best_bid, best_ask
+FROM market_prices;
+
+DROP TABLE market_prices;
+ALTER TABLE market_prices_new RENAME TO market_prices;
+
+CREATE INDEX idx_market_prices_updated ON market_prices(updated_at);
+CREATE INDEX idx_market_prices_location ON market_prices(location_type, location_id);
+
+CREATE TABLE market_price_history_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    type_id INTEGER NOT NULL,
+    location_type TEXT NOT NULL CHECK (location_type IN ('region', 'station', 'global')),
+    location_id INTEGER NOT NULL,
+    sell_price REAL,
+    buy_price REAL,
+    best_bid REAL,
+    best_ask REAL,
+    daily_volume INTEGER,
+    recorded_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (type_id) REFERENCES eve_types(type_id)
+);
+
+INSERT INTO market_price_history_new
+    (id, type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask, daily_volume, recorded_at)
+SELECT id, type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask, daily_volume, recorded_at
+FROM market_price_history;
+
+DROP TABLE market_price_history;
+ALTER TABLE market_price_history_new RENAME TO market_price_history;
+
+CREATE INDEX idx_market_price_history_lookup
+    ON market_price_history(type_id, location_type, location_id, recorded_at);
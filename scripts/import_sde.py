@@ -144,6 +144,7 @@ def load_blueprints():
         if product_type_id:
             blueprints_map[blueprint_type_id] = {
                 'product_type_id': product_type_id,
+                'product_quantity': product.get('quantity', 1),
                 'manufacturing_time': time,
                 'materials': materials
             }
@@ -234,6 +235,7 @@ def import_to_database():
             if type_id in blueprints_map:
                 blueprint = blueprints_map[type_id]
                 product_type_id = blueprint['product_type_id']
+                product_quantity = blueprint['product_quantity']
                 manufacturing_time = blueprint['manufacturing_time']
 
                 # Skip if the product type doesn't exist in type_info (deprecated/removed items)
@@ -242,9 +244,9 @@ def import_to_database():
                     continue
 
                 cursor.execute("""
-                    INSERT OR IGNORE INTO blueprints (blueprint_type_id, product_type_id, manufacturing_time)
-                    VALUES (?, ?, ?)
-                """, (type_id, product_type_id, manufacturing_time))
+                    INSERT OR IGNORE INTO blueprints (blueprint_type_id, product_type_id, product_quantity, manufacturing_time)
+                    VALUES (?, ?, ?, ?)
+                """, (type_id, product_type_id, product_quantity, manufacturing_time))
 
                 if cursor.rowcount > 0:
                     blueprint_count += 1
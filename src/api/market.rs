@@ -1,15 +1,42 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::{
-    db::{models::MarketPrice, queries, DbPool},
+    db::{
+        models::{MarketLocation, MarketPrice, MarketPriceHistory, PriceRefreshRun},
+        queries, DbPool,
+    },
     error::{ApiError, ApiResult},
-    external::fuzzworks::FuzzworksClient,
+    external::price_source::{self, PriceSourceKind, PriceSources, SourcePrice},
+    services::{
+        market_data, order_book,
+        price_history::{self, MovingAveragePoint, PriceStats},
+        price_refresh::RefreshConfig,
+    },
 };
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateMarketPricesRequest {
     pub type_ids: Vec<i32>,
+    /// Where to price (default: The Forge, or the global location for a global source);
+    /// global sources only price the global location and the others never do
+    #[serde(default)]
+    pub location: Option<MarketLocation>,
+    /// Where prices come from (default: PRICE_SOURCE, or ESI averages for an empty type_ids)
+    #[serde(default)]
+    pub source: Option<PriceSourceKind>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketPricesRequest {
+    pub type_ids: Vec<i32>,
+    /// Region or station whose cached prices are returned (default: The Forge)
+    #[serde(default)]
+    pub location: MarketLocation,
 }
 
 #[derive(Debug, Serialize)]
@@ -23,92 +50,362 @@ pub struct MarketPriceOutput {
     pub type_id: i32,
     pub sell_price: Option<f64>,
     pub buy_price: Option<f64>,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrderBookRequest {
+    /// Region whose order book is fetched (default: The Forge)
+    #[serde(default = "default_region_id")]
+    pub region_id: i32,
+    /// Only count orders at this station (default: Jita 4-4); null for the whole region
+    #[serde(default = "default_station_id")]
+    pub station_id: Option<i64>,
+    /// Types to price; empty prices every type with orders
+    #[serde(default)]
+    pub type_ids: Vec<i32>,
+}
+
+fn default_region_id() -> i32 {
+    MarketLocation::THE_FORGE.id() as i32
 }
 
-/// Update market prices from ESI API
-/// If type_ids is empty, fetches and stores ALL prices from ESI (bulk backfill)
+fn default_station_id() -> Option<i64> {
+    Some(order_book::JITA_STATION_ID)
+}
+
+/// Update market prices
+/// Requested type_ids are priced from the request's price source, or the configured default
+/// (PRICE_SOURCE), at the requested location.
+/// If type_ids is empty and no source is given, fetches ALL ESI global average prices and stores
+/// them at the global location (bulk backfill)
 pub async fn update_market_prices(
     State(pool): State<DbPool>,
+    Extension(sources): Extension<PriceSources>,
     Json(request): Json<UpdateMarketPricesRequest>,
 ) -> ApiResult<Json<UpdateMarketPricesResponse>> {
-    let esi_client = FuzzworksClient::new();
-    let market_data = esi_client
-        .get_market_data(&request.type_ids)
-        .await
-        .map_err(|e| ApiError::ExternalApi(format!("ESI API error: {}", e)))?;
+    let kind = match request.source {
+        Some(kind) => kind,
+        None if request.type_ids.is_empty() => PriceSourceKind::EsiAverage,
+        None => sources.default_kind(),
+    };
+    let source = sources.get(kind)?;
 
-    let mut updated_count = 0;
-    let mut prices_output = Vec::new();
+    // Global prices would otherwise pass for a hub's own
+    let location = match request.location {
+        Some(location) => location,
+        None if source.is_global() => MarketLocation::Global,
+        None => MarketLocation::THE_FORGE,
+    };
+    price_source::check_location(source.as_ref(), location)?;
 
-    // If type_ids is empty, store ALL prices from ESI (backfill mode)
-    // Otherwise, only store requested type_ids
-    let type_ids_to_process: Vec<i32> = if request.type_ids.is_empty() {
-        tracing::info!("Backfilling ALL market prices from ESI");
-        market_data.keys().copied().collect()
+    if request.type_ids.is_empty() {
+        tracing::info!("Backfilling ALL market prices from {}", source.name());
     } else {
-        tracing::info!("Fetching market prices for {} types", request.type_ids.len());
-        request.type_ids.clone()
+        tracing::info!(
+            "Fetching market prices for {} types at {} from {}",
+            request.type_ids.len(),
+            location,
+            source.name()
+        );
+    }
+
+    let fetched = source.fetch_prices(location, &request.type_ids).await?;
+
+    let response = store_prices(&pool, location, &fetched.prices, &request.type_ids).await?;
+    Ok(Json(response))
+}
+
+/// Update market prices from a region's ESI order book, optionally only at one hub station
+/// Sell and buy prices are 5% percentiles; best bid/ask are the top of the book
+pub async fn update_order_book_prices(
+    State(pool): State<DbPool>,
+    Extension(sources): Extension<PriceSources>,
+    Json(request): Json<UpdateOrderBookRequest>,
+) -> ApiResult<Json<UpdateMarketPricesResponse>> {
+    tracing::info!(
+        "Fetching order book for region {} (station {:?})",
+        request.region_id,
+        request.station_id
+    );
+
+    let book = order_book::fetch_order_book(
+        sources.esi(),
+        request.region_id,
+        request.station_id,
+        &request.type_ids,
+    )
+    .await?;
+
+    let prices: HashMap<i32, SourcePrice> = book
+        .summaries
+        .into_iter()
+        .map(|(type_id, summary)| {
+            (type_id, SourcePrice {
+                sell_price: summary.sell_percentile,
+                buy_price: summary.buy_percentile,
+                best_bid: summary.best_bid,
+                best_ask: summary.best_ask,
+            })
+        })
+        .collect();
+
+    // Station prices are cached for the station, region-wide prices for the region
+    let location = match request.station_id {
+        Some(station_id) => MarketLocation::Station(station_id),
+        None => MarketLocation::Region(request.region_id),
     };
 
-    for type_id in &type_ids_to_process {
-        if let Some(aggregate) = market_data.get(type_id) {
-            let market_price = MarketPrice {
-                type_id: *type_id,
-                region_id: 10000002, // Global average (not region-specific)
-                sell_price: Some(aggregate.sell.weighted_average),
-                buy_price: Some(aggregate.buy.weighted_average),
-                daily_volume: Some(aggregate.sell.volume as i32),
-                updated_at: chrono::Utc::now().to_rfc3339(),
-            };
-
-            // Try to insert, but skip if the type_id doesn't exist in eve_types (foreign key constraint)
-            match queries::upsert_market_price(&pool, &market_price).await {
-                Ok(_) => {
-                    // Only return individual prices if not in backfill mode (to avoid huge response)
-                    if !request.type_ids.is_empty() {
-                        prices_output.push(MarketPriceOutput {
-                            type_id: *type_id,
-                            sell_price: market_price.sell_price,
-                            buy_price: market_price.buy_price,
-                        });
-                    }
-
-                    updated_count += 1;
-
-                    // Log progress every 1000 items in backfill mode
-                    if request.type_ids.is_empty() && updated_count % 1000 == 0 {
-                        tracing::info!("Backfilled {} prices...", updated_count);
-                    }
-                }
-                Err(e) => {
-                    // Skip foreign key constraint errors (type doesn't exist in our database)
-                    let err_str = format!("{:?}", e);
-                    if !err_str.contains("FOREIGN KEY constraint") {
-                        // If it's not a foreign key error, return it
-                        return Err(ApiError::Database(e));
-                    }
-                    // Silently skip foreign key errors
-                }
-            }
-        }
-    }
+    let response = store_prices(&pool, location, &prices, &request.type_ids).await?;
+    Ok(Json(response))
+}
+
+/// Store fetched prices; an empty `requested` list stores everything (backfill mode)
+async fn store_prices(
+    pool: &DbPool,
+    location: MarketLocation,
+    prices: &HashMap<i32, SourcePrice>,
+    requested: &[i32],
+) -> ApiResult<UpdateMarketPricesResponse> {
+    let market_prices = market_data::store_prices(pool, location, prices, requested).await?;
+
+    let updated_count = market_prices.len();
+
+    // Only return individual prices if not in backfill mode (to avoid huge response)
+    let prices_output = if requested.is_empty() {
+        Vec::new()
+    } else {
+        market_prices
+            .into_iter()
+            .map(|price| MarketPriceOutput {
+                type_id: price.type_id,
+                sell_price: price.sell_price,
+                buy_price: price.buy_price,
+                best_bid: price.best_bid,
+                best_ask: price.best_ask,
+            })
+            .collect()
+    };
 
-    tracing::info!("Updated {} market prices", updated_count);
+    tracing::info!("Updated {} market prices at {}", updated_count, location);
 
-    Ok(Json(UpdateMarketPricesResponse {
+    Ok(UpdateMarketPricesResponse {
         updated_count,
         prices: prices_output,
-    }))
+    })
 }
 
-/// Get cached market prices
+/// Get cached market prices at a location
 pub async fn get_market_prices(
     State(pool): State<DbPool>,
-    Json(request): Json<UpdateMarketPricesRequest>,
+    Json(request): Json<MarketPricesRequest>,
 ) -> ApiResult<Json<Vec<MarketPrice>>> {
-    let prices = queries::get_market_prices(&pool, &request.type_ids)
+    let prices = queries::get_market_prices(&pool, &request.type_ids, request.location)
         .await
         .map_err(ApiError::Database)?;
 
     Ok(Json(prices))
 }
+
+/// Longest period price history can be requested for
+const MAX_HISTORY_DAYS: i64 = 365;
+
+#[derive(Debug, Deserialize)]
+pub struct PriceHistoryQuery {
+    /// Region to read history for (default: The Forge)
+    pub region: Option<i32>,
+    /// Station to read history for; takes precedence over `region`
+    pub station: Option<i64>,
+    /// Read the global location's history (ESI averages) instead of a region or station
+    #[serde(default)]
+    pub global: bool,
+    /// How many days back to look (default: 30)
+    #[serde(default = "default_history_days")]
+    pub days: i64,
+    /// Moving average window in days (default: 7)
+    #[serde(default = "default_average_window")]
+    pub window: usize,
+}
+
+fn default_history_days() -> i64 {
+    30
+}
+
+fn default_average_window() -> usize {
+    7
+}
+
+impl PriceHistoryQuery {
+    fn location(&self) -> MarketLocation {
+        if self.global {
+            return MarketLocation::Global;
+        }
+        match (self.station, self.region) {
+            (Some(station_id), _) => MarketLocation::Station(station_id),
+            (None, Some(region_id)) => MarketLocation::Region(region_id),
+            (None, None) => MarketLocation::THE_FORGE,
+        }
+    }
+
+    fn validate(&self) -> ApiResult<()> {
+        if !(1..=MAX_HISTORY_DAYS).contains(&self.days) {
+            return Err(ApiError::InvalidInput(format!(
+                "days must be between 1 and {}",
+                MAX_HISTORY_DAYS
+            )));
+        }
+        if self.window == 0 {
+            return Err(ApiError::InvalidInput("window must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceHistoryResponse {
+    pub type_id: i32,
+    pub location: MarketLocation,
+    pub days: i64,
+    pub history: Vec<MarketPriceHistory>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MovingAverageResponse {
+    pub type_id: i32,
+    pub location: MarketLocation,
+    pub days: i64,
+    pub window: usize,
+    pub points: Vec<MovingAveragePoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VolatilityResponse {
+    pub type_id: i32,
+    pub location: MarketLocation,
+    pub days: i64,
+    /// Statistics of the daily average sell price; None without sell history
+    pub sell: Option<PriceStats>,
+    pub buy: Option<PriceStats>,
+}
+
+/// Fail with NotFound for types that don't exist
+async fn ensure_type_exists(pool: &DbPool, type_id: i32) -> ApiResult<()> {
+    queries::get_type_by_id(pool, type_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Type {} not found", type_id)))?;
+    Ok(())
+}
+
+/// Get every price refresh recorded for a type at a location
+pub async fn get_price_history(
+    State(pool): State<DbPool>,
+    Path(type_id): Path<i32>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> ApiResult<Json<PriceHistoryResponse>> {
+    query.validate()?;
+    ensure_type_exists(&pool, type_id).await?;
+
+    let location = query.location();
+    let history = queries::get_price_history(&pool, type_id, location, query.days)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(PriceHistoryResponse {
+        type_id,
+        location,
+        days: query.days,
+        history,
+    }))
+}
+
+/// Get a type's daily prices with trailing moving averages
+pub async fn get_moving_averages(
+    State(pool): State<DbPool>,
+    Path(type_id): Path<i32>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> ApiResult<Json<MovingAverageResponse>> {
+    query.validate()?;
+    ensure_type_exists(&pool, type_id).await?;
+
+    let location = query.location();
+    let daily = queries::get_daily_prices(&pool, type_id, location, query.days)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(MovingAverageResponse {
+        type_id,
+        location,
+        days: query.days,
+        window: query.window,
+        points: price_history::moving_averages(&daily, query.window),
+    }))
+}
+
+/// Get the volatility of a type's daily prices, to tell a price spike from a trend
+pub async fn get_price_volatility(
+    State(pool): State<DbPool>,
+    Path(type_id): Path<i32>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> ApiResult<Json<VolatilityResponse>> {
+    query.validate()?;
+    ensure_type_exists(&pool, type_id).await?;
+
+    let location = query.location();
+    let daily = queries::get_daily_prices(&pool, type_id, location, query.days)
+        .await
+        .map_err(ApiError::Database)?;
+
+    let sell: Vec<f64> = daily.iter().filter_map(|d| d.sell_price).collect();
+    let buy: Vec<f64> = daily.iter().filter_map(|d| d.buy_price).collect();
+
+    Ok(Json(VolatilityResponse {
+        type_id,
+        location,
+        days: query.days,
+        sell: price_history::price_stats(&sell),
+        buy: price_history::price_stats(&buy),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshStatusResponse {
+    /// Whether the server refreshes prices in the background (PRICE_REFRESH_INTERVAL_MINUTES > 0)
+    pub enabled: bool,
+    pub interval_minutes: Option<u64>,
+    /// Price source refreshes fetch from (PRICE_SOURCE)
+    pub source: PriceSourceKind,
+    /// Whether a refresh is in progress
+    pub running: bool,
+    /// When the next refresh is due (UTC); None while one is running or before the first run
+    pub next_run_at: Option<String>,
+    pub last_run: Option<PriceRefreshRun>,
+    pub last_success: Option<PriceRefreshRun>,
+}
+
+/// Get the status of the background price refresh
+pub async fn get_refresh_status(
+    State(pool): State<DbPool>,
+    Extension(sources): Extension<PriceSources>,
+    Extension(config): Extension<Option<RefreshConfig>>,
+) -> ApiResult<Json<RefreshStatusResponse>> {
+    let last_run = queries::get_last_price_refresh_run(&pool, None)
+        .await
+        .map_err(ApiError::Database)?;
+    let last_success = queries::get_last_price_refresh_run(&pool, Some("success"))
+        .await
+        .map_err(ApiError::Database)?;
+
+    let running = last_run.as_ref().is_some_and(|run| run.status == "running");
+
+    Ok(Json(RefreshStatusResponse {
+        enabled: config.is_some(),
+        interval_minutes: config.map(|config| config.interval.as_secs() / 60),
+        source: sources.default_kind(),
+        running,
+        next_run_at: last_run.as_ref().and_then(|run| run.next_run_at.clone()),
+        last_run,
+        last_success,
+    }))
+}
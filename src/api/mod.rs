@@ -1,5 +1,8 @@
 pub mod market;
+pub mod npcs;
 pub mod salvage;
+pub mod types;
+pub mod universe;
 
 use axum::{routing::{get, post}, Router};
 use crate::db::DbPool;
@@ -8,5 +11,23 @@ pub fn routes() -> Router<DbPool> {
     Router::new()
         .route("/salvage/analyze", post(salvage::analyze_salvage))
         .route("/market/update", post(market::update_market_prices))
+        .route("/market/order-book", post(market::update_order_book_prices))
         .route("/market/prices", post(market::get_market_prices))
+        .route("/market/refresh-status", get(market::get_refresh_status))
+        .route("/market/history/:type_id", get(market::get_price_history))
+        .route("/market/history/:type_id/moving-average", get(market::get_moving_averages))
+        .route("/market/history/:type_id/volatility", get(market::get_price_volatility))
+        .route("/types/:type_id", get(types::get_type))
+        .route("/categories", get(types::list_categories))
+        .route("/categories/:category_id/groups", get(types::list_category_groups))
+        .route("/market-groups", get(types::get_market_group_tree))
+        .route("/market-groups/:market_group_id/types", get(types::list_market_group_types))
+        .route("/regions", get(universe::list_regions))
+        .route("/regions/:region_id/systems", get(universe::list_region_systems))
+        .route("/systems", get(universe::search_systems))
+        .route("/systems/:solar_system_id", get(universe::get_system))
+        .route("/stations/:station_id", get(universe::get_station))
+        .route("/npcs", get(npcs::list_npcs))
+        .route("/npcs/salvage", post(npcs::estimate_kill_salvage))
+        .route("/npcs/:type_id/salvage", get(npcs::get_npc_salvage))
 }
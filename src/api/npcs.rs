@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    api::salvage::SalvageRequestItem,
+    db::{models::*, queries, DbPool},
+    error::{ApiError, ApiResult},
+};
+
+const WRECK_SIZES: [&str; 3] = ["small", "medium", "large"];
+
+#[derive(Debug, Deserialize)]
+pub struct NpcQuery {
+    /// Faction key (e.g. "serpentis")
+    pub faction: Option<String>,
+    /// "small", "medium" or "large"
+    pub wreck_size: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpectedSalvage {
+    pub type_id: i32,
+    pub name: String,
+    pub drop_chance: f64,
+    pub avg_quantity: f64,
+    /// Average units per salvaged wreck (drop_chance * avg_quantity)
+    pub expected_quantity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NpcSalvage {
+    #[serde(flatten)]
+    pub npc: NpcType,
+    pub salvage: Vec<ExpectedSalvage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KillSalvageRequest {
+    pub kills: Vec<NpcKill>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NpcKill {
+    /// NPC ship name in any language (e.g. "Coreli Guardian Initiate")
+    pub name: String,
+    pub count: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SalvageTotal {
+    pub type_id: i32,
+    pub name: String,
+    pub expected_quantity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KillSalvageResponse {
+    pub salvage: Vec<SalvageTotal>,
+    /// Expected salvage rounded to whole units, ready to send to /api/salvage/analyze
+    pub salvage_items: Vec<SalvageRequestItem>,
+    /// Names that are not known pirate NPC ships
+    pub unknown_npcs: Vec<String>,
+}
+
+fn validate_wreck_size(wreck_size: Option<&str>) -> ApiResult<()> {
+    match wreck_size {
+        Some(size) if !WRECK_SIZES.contains(&size) => Err(ApiError::InvalidInput(format!(
+            "wreck_size must be one of {}",
+            WRECK_SIZES.join(", ")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// List pirate NPC ships (`?faction=serpentis&wreck_size=small`)
+pub async fn list_npcs(
+    State(pool): State<DbPool>,
+    Query(query): Query<NpcQuery>,
+) -> ApiResult<Json<Vec<NpcType>>> {
+    let faction = query.faction.map(|f| f.to_lowercase());
+    let wreck_size = query.wreck_size.map(|s| s.to_lowercase());
+    validate_wreck_size(wreck_size.as_deref())?;
+
+    let npcs = queries::get_npc_types(&pool, faction.as_deref(), wreck_size.as_deref())
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(npcs))
+}
+
+/// Get the salvage expected from one wreck of an NPC ship
+pub async fn get_npc_salvage(
+    State(pool): State<DbPool>,
+    Path(type_id): Path<i32>,
+) -> ApiResult<Json<NpcSalvage>> {
+    let npc = queries::get_npc_type(&pool, type_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("NPC ship {} not found", type_id)))?;
+
+    let salvage = queries::get_wreck_salvage(&pool, &npc.faction, &npc.wreck_size)
+        .await
+        .map_err(ApiError::Database)?
+        .into_iter()
+        .map(|s| ExpectedSalvage {
+            type_id: s.salvage_type_id,
+            name: s.salvage_name,
+            drop_chance: s.drop_chance,
+            avg_quantity: s.avg_quantity,
+            expected_quantity: s.drop_chance * s.avg_quantity,
+        })
+        .collect();
+
+    Ok(Json(NpcSalvage { npc, salvage }))
+}
+
+/// Estimate the salvage from a list of killed NPC ships
+pub async fn estimate_kill_salvage(
+    State(pool): State<DbPool>,
+    Json(request): Json<KillSalvageRequest>,
+) -> ApiResult<Json<KillSalvageResponse>> {
+    tracing::info!("Estimating salvage for {} NPC kills", request.kills.len());
+
+    // Wrecks killed per (faction, wreck size)
+    let mut wrecks: HashMap<(String, String), i32> = HashMap::new();
+    let mut unknown_npcs = Vec::new();
+
+    for kill in &request.kills {
+        if kill.count <= 0 {
+            return Err(ApiError::InvalidInput(format!(
+                "Kill count for '{}' must be positive",
+                kill.name
+            )));
+        }
+
+        let npc = match queries::get_type_by_name(&pool, &kill.name)
+            .await
+            .map_err(ApiError::Database)?
+        {
+            Some(eve_type) => queries::get_npc_type(&pool, eve_type.type_id)
+                .await
+                .map_err(ApiError::Database)?,
+            None => None,
+        };
+
+        match npc {
+            Some(npc) => *wrecks.entry((npc.faction, npc.wreck_size)).or_insert(0) += kill.count,
+            None => {
+                tracing::warn!("NPC '{}' is not a known pirate ship", kill.name);
+                unknown_npcs.push(kill.name.clone());
+            }
+        }
+    }
+
+    let mut totals: HashMap<i32, SalvageTotal> = HashMap::new();
+    for ((faction, wreck_size), count) in &wrecks {
+        let salvage = queries::get_wreck_salvage(&pool, faction, wreck_size)
+            .await
+            .map_err(ApiError::Database)?;
+
+        for s in salvage {
+            totals
+                .entry(s.salvage_type_id)
+                .or_insert_with(|| SalvageTotal {
+                    type_id: s.salvage_type_id,
+                    name: s.salvage_name.clone(),
+                    expected_quantity: 0.0,
+                })
+                .expected_quantity += s.drop_chance * s.avg_quantity * *count as f64;
+        }
+    }
+
+    let mut salvage: Vec<SalvageTotal> = totals.into_values().collect();
+    salvage.sort_by(|a, b| {
+        b.expected_quantity
+            .partial_cmp(&a.expected_quantity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let salvage_items = salvage
+        .iter()
+        .map(|s| SalvageRequestItem {
+            name: s.name.clone(),
+            quantity: s.expected_quantity.round() as i32,
+        })
+        .filter(|item| item.quantity > 0)
+        .collect();
+
+    Ok(Json(KillSalvageResponse {
+        salvage,
+        salvage_items,
+        unknown_npcs,
+    }))
+}
@@ -1,10 +1,16 @@
-use axum::{extract::State, Json};
+use axum::{extract::State, Extension, Json};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::{
     db::{models::*, queries, DbPool},
     error::{ApiError, ApiResult},
-    services::{blueprint_matcher, market_data, material_calculator},
+    external::price_source::PriceSources,
+    services::{
+        blueprint_matcher,
+        market_data::{self, PriceStatus},
+        material_calculator, price_refresh,
+    },
 };
 
 #[derive(Debug, Deserialize)]
@@ -12,13 +18,48 @@ pub struct AnalyzeSalvageRequest {
     pub salvage_items: Vec<SalvageRequestItem>,
     #[serde(default = "default_reprocessing_efficiency")]
     pub reprocessing_efficiency: Option<f64>,
+    /// Only return buildable items whose product is in one of these categories (e.g. "Module")
+    #[serde(default)]
+    pub product_categories: Vec<String>,
+    /// Only return buildable rigs of these sizes ("small", "medium", "large", "capital")
+    #[serde(default)]
+    pub rig_sizes: Vec<String>,
+    /// Only return buildable items of these tech levels (e.g. [1] for T1 only)
+    #[serde(default)]
+    pub tech_levels: Vec<i32>,
+    /// Language for returned item names (SDE code, e.g. "de", "ru"); defaults to English.
+    /// Salvage item names are matched in any language regardless.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Character skill levels by skill name (e.g. {"Reprocessing": 5, "Scrapmetal Processing": 4}).
+    /// When set, reprocessing efficiency is calculated per item from the character's skills
+    /// instead of `reprocessing_efficiency`, and buildable items list missing manufacturing skills.
+    #[serde(default)]
+    pub skills: HashMap<String, u8>,
+    /// Where materials and products are sold (default: The Forge), e.g. {"station": 60003760}
+    #[serde(default)]
+    pub sell_hub: MarketLocation,
+    /// Where missing materials are bought (default: The Forge), e.g. {"station": 60008494}
+    #[serde(default)]
+    pub buy_hub: MarketLocation,
+    /// Prices cached longer ago than this many minutes are stale (default: one day)
+    #[serde(default = "default_max_price_age_minutes")]
+    pub max_price_age_minutes: i64,
+    /// Re-price stale and missing prices from ESI order books before analyzing;
+    /// otherwise they are only reported in `warnings`
+    #[serde(default)]
+    pub refresh_stale_prices: bool,
 }
 
 fn default_reprocessing_efficiency() -> Option<f64> {
     Some(0.5) // 50% default
 }
 
-#[derive(Debug, Deserialize)]
+fn default_max_price_age_minutes() -> i64 {
+    24 * 60
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SalvageRequestItem {
     pub name: String,
     pub quantity: i32,
@@ -29,8 +70,24 @@ pub struct AnalyzeSalvageResponse {
     pub materials: Vec<MaterialOutput>,
     pub total_material_value: f64,
     pub buildable_items: Vec<BuildableItem>,
+    /// Efficiency applied to items without a specific processing skill
     pub reprocessing_efficiency_used: f64,
+    pub reprocessed_items: Vec<ReprocessedItem>,
     pub unknown_items: Vec<UnknownItem>,
+    pub sell_hub: MarketLocation,
+    pub buy_hub: MarketLocation,
+    /// Stale or missing prices, and refreshes that failed
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReprocessedItem {
+    pub type_id: i32,
+    pub name: String,
+    pub quantity: i32,
+    /// Specific processing skill for this item (e.g. "Scrapmetal Processing")
+    pub processing_skill: Option<String>,
+    pub efficiency: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,19 +103,53 @@ pub struct MaterialOutput {
     pub quantity: i32,
     pub unit_price: Option<f64>,
     pub total_value: f64,
+    /// Minutes since the price was cached; None when missing
+    pub price_age_minutes: Option<i64>,
+    pub price_status: PriceStatus,
 }
 
 #[derive(Debug, Serialize)]
 pub struct BuildableItem {
     pub product_type_id: i32,
     pub product_name: String,
+    pub group_name: Option<String>,
+    pub category_name: Option<String>,
+    pub tech_level: Option<i32>,
+    pub meta_level: Option<i32>,
+    /// Rig size label ("small", "medium", "large", "capital"); None for non-rigs
+    pub rig_size: Option<String>,
+    /// Calibration the rig uses
+    pub calibration: Option<i32>,
+    /// Fitting slot ("high", "medium", "low", "rig", "subsystem")
+    pub slot: Option<String>,
     pub match_percentage: f64,
     pub can_build: bool,
     pub estimated_profit: f64,
     pub profit_margin: f64,
+    /// Sell price of one unit of the product at the sell hub
     pub product_price: f64,
+    /// Units produced per run
+    pub product_quantity: i32,
+    /// Value of one run's output (product_price * product_quantity)
+    pub product_value: f64,
     pub material_cost: f64,
     pub missing_materials: Vec<MissingMaterial>,
+    /// Whether the character has every manufacturing skill; None when no skills were given
+    pub has_required_skills: Option<bool>,
+    pub missing_skills: Vec<MissingSkill>,
+    /// Age in minutes of the oldest price used (product and missing materials); None when
+    /// any of them is missing
+    pub price_age_minutes: Option<i64>,
+    /// Worst status of the prices used: "missing" over "stale" over "fresh"
+    pub price_status: PriceStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissingSkill {
+    pub skill_type_id: i32,
+    pub name: String,
+    pub required_level: i32,
+    pub trained_level: i32,
 }
 
 #[derive(Debug, Serialize)]
@@ -74,12 +165,19 @@ pub struct MissingMaterial {
 /// Analyze salvage and determine what can be built
 pub async fn analyze_salvage(
     State(pool): State<DbPool>,
+    Extension(sources): Extension<PriceSources>,
     Json(request): Json<AnalyzeSalvageRequest>,
 ) -> ApiResult<Json<AnalyzeSalvageResponse>> {
     tracing::info!("Analyzing {} salvage items", request.salvage_items.len());
 
     let reprocessing_efficiency = request.reprocessing_efficiency.unwrap_or(0.5);
 
+    if request.max_price_age_minutes <= 0 {
+        return Err(ApiError::InvalidInput(
+            "max_price_age_minutes must be positive".to_string(),
+        ));
+    }
+
     // Step 1: Look up salvage items in database
     let mut salvage_inputs = Vec::new();
     let mut unknown_items = Vec::new();
@@ -104,6 +202,35 @@ pub async fn analyze_salvage(
         }
     }
 
+    // Resolve character skills to skill type IDs
+    let mut trained_skills: HashMap<i32, u8> = HashMap::new();
+    for (skill_name, level) in &request.skills {
+        if *level > 5 {
+            return Err(ApiError::InvalidInput(format!(
+                "Skill level for '{}' must be between 0 and 5",
+                skill_name
+            )));
+        }
+
+        let skill = queries::get_type_by_name(&pool, skill_name)
+            .await
+            .map_err(ApiError::Database)?
+            .ok_or_else(|| ApiError::InvalidInput(format!("Unknown skill '{}'", skill_name)))?;
+        trained_skills.insert(skill.type_id, *level);
+    }
+    let use_skills = !request.skills.is_empty();
+    let trained_level = |skill_type_id: i32| trained_skills.get(&skill_type_id).copied().unwrap_or(0);
+
+    let reprocessing_efficiency = if use_skills {
+        material_calculator::calculate_reprocessing_efficiency(
+            trained_level(material_calculator::REPROCESSING_SKILL_ID),
+            trained_level(material_calculator::REPROCESSING_EFFICIENCY_SKILL_ID),
+            0,
+        )
+    } else {
+        reprocessing_efficiency
+    };
+
     // If all items are unknown, return empty analysis with unknown items
     if salvage_inputs.is_empty() && !unknown_items.is_empty() {
         return Ok(Json(AnalyzeSalvageResponse {
@@ -111,17 +238,40 @@ pub async fn analyze_salvage(
             total_material_value: 0.0,
             buildable_items: Vec::new(),
             reprocessing_efficiency_used: reprocessing_efficiency,
+            reprocessed_items: Vec::new(),
             unknown_items,
+            sell_hub: request.sell_hub,
+            buy_hub: request.buy_hub,
+            warnings: Vec::new(),
         }));
     }
 
-    // Step 2: Calculate reprocessed materials
-    let materials = material_calculator::calculate_materials(
-        &pool,
-        &salvage_inputs,
-        reprocessing_efficiency,
-    )
-    .await?;
+    // Step 2: Calculate reprocessed materials, using each item's specific processing skill
+    let salvage_type_ids: Vec<i32> = salvage_inputs.iter().map(|s| s.type_id).collect();
+    let processing_skills: HashMap<i32, i32> = queries::get_reprocessing_skills(&pool, &salvage_type_ids)
+        .await
+        .map_err(ApiError::Database)?
+        .into_iter()
+        .map(|s| (s.source_type_id, s.skill_type_id))
+        .collect();
+
+    let item_efficiency = |salvage: &SalvageInput| {
+        if !use_skills {
+            return reprocessing_efficiency;
+        }
+
+        material_calculator::calculate_reprocessing_efficiency(
+            trained_level(material_calculator::REPROCESSING_SKILL_ID),
+            trained_level(material_calculator::REPROCESSING_EFFICIENCY_SKILL_ID),
+            processing_skills
+                .get(&salvage.type_id)
+                .map(|skill_type_id| trained_level(*skill_type_id))
+                .unwrap_or(0),
+        )
+    };
+
+    let materials =
+        material_calculator::calculate_materials_with(&pool, &salvage_inputs, item_efficiency).await?;
 
     tracing::info!("Reprocessed into {} different materials", materials.len());
 
@@ -138,10 +288,142 @@ pub async fn analyze_salvage(
 
     // Collect product type IDs for price lookup
     let product_type_ids: Vec<i32> = blueprint_matches.iter().map(|bm| bm.product.type_id).collect();
-    all_type_ids.extend(product_type_ids);
+    all_type_ids.extend(product_type_ids.iter().copied());
+
+    // Materials and products are valued at the sell hub
+    let mut all_prices = market_data::get_cached_prices(&pool, &all_type_ids, request.sell_hub).await?;
+
+    // Missing materials are bought at the buy hub
+    let missing_type_ids: Vec<i32> = blueprint_matches
+        .iter()
+        .flat_map(|bm| bm.missing_materials.iter().map(|m| m.type_id))
+        .collect();
+    let mut buy_prices = market_data::get_cached_prices(&pool, &missing_type_ids, request.buy_hub).await?;
+
+    let max_age = request.max_price_age_minutes;
+    let mut warnings = Vec::new();
+
+    if request.refresh_stale_prices {
+        let sell_refreshed =
+            refresh_stale_prices(&pool, &sources, request.sell_hub, &all_type_ids, &all_prices, max_age).await;
+        let buy_refreshed =
+            refresh_stale_prices(&pool, &sources, request.buy_hub, &missing_type_ids, &buy_prices, max_age).await;
+
+        for refreshed in [&sell_refreshed, &buy_refreshed] {
+            if let Err(warning) = refreshed {
+                warnings.push(warning.clone());
+            }
+        }
+        if sell_refreshed.is_ok_and(|count| count > 0) {
+            all_prices = market_data::get_cached_prices(&pool, &all_type_ids, request.sell_hub).await?;
+        }
+        if buy_refreshed.is_ok_and(|count| count > 0) {
+            buy_prices = market_data::get_cached_prices(&pool, &missing_type_ids, request.buy_hub).await?;
+        }
+    }
 
-    // Get all prices (materials + products)
-    let all_prices = market_data::get_cached_prices(&pool, &all_type_ids).await?;
+    let now = chrono::Utc::now();
+    warnings.extend(price_warnings(request.sell_hub, &all_type_ids, &all_prices, max_age, now));
+    warnings.extend(price_warnings(request.buy_hub, &missing_type_ids, &buy_prices, max_age, now));
+
+    // Age and status of a type's price
+    let freshness = |prices: &HashMap<i32, MarketPrice>, type_id: i32| {
+        let price = prices.get(&type_id);
+        let age = price
+            .filter(|p| p.sell_price.is_some())
+            .and_then(|p| market_data::price_age_minutes(p, now));
+        (age, market_data::price_status(price, max_age, now))
+    };
+
+    // Manufacturing skill requirements of the matched blueprints
+    let mut blueprint_skills: HashMap<i32, Vec<BlueprintSkill>> = HashMap::new();
+    if use_skills {
+        let blueprint_type_ids: Vec<i32> = blueprint_matches
+            .iter()
+            .map(|bm| bm.blueprint.blueprint_type_id)
+            .collect();
+        for skill in queries::get_blueprint_skills(&pool, &blueprint_type_ids)
+            .await
+            .map_err(ApiError::Database)?
+        {
+            blueprint_skills.entry(skill.blueprint_type_id).or_default().push(skill);
+        }
+    }
+
+    let mut skill_type_ids: Vec<i32> = processing_skills.values().copied().collect();
+    skill_type_ids.extend(blueprint_skills.values().flatten().map(|s| s.skill_type_id));
+    skill_type_ids.sort_unstable();
+    skill_type_ids.dedup();
+
+    let skill_names: HashMap<i32, String> = queries::get_types_by_ids(&pool, &skill_type_ids)
+        .await
+        .map_err(ApiError::Database)?
+        .into_iter()
+        .map(|t| (t.type_id, t.name))
+        .collect();
+
+    // Localized names for everything in the response
+    let names: HashMap<i32, String> = match request.language.as_deref().map(str::to_lowercase) {
+        Some(language) if language != "en" => {
+            let mut name_type_ids = all_type_ids.clone();
+            name_type_ids.extend(
+                blueprint_matches
+                    .iter()
+                    .flat_map(|bm| bm.missing_materials.iter().map(|m| m.type_id)),
+            );
+            name_type_ids.extend(salvage_type_ids.iter().copied());
+            name_type_ids.extend(skill_type_ids.iter().copied());
+            name_type_ids.sort_unstable();
+            name_type_ids.dedup();
+
+            queries::get_type_names(&pool, &name_type_ids, &language)
+                .await
+                .map_err(ApiError::Database)?
+                .into_iter()
+                .map(|n| (n.type_id, n.name))
+                .collect()
+        }
+        _ => HashMap::new(),
+    };
+    let localized = |type_id: i32, english: &str| {
+        names.get(&type_id).cloned().unwrap_or_else(|| english.to_string())
+    };
+    let skill_name = |skill_type_id: i32| {
+        let english = skill_names
+            .get(&skill_type_id)
+            .cloned()
+            .unwrap_or_else(|| format!("Skill {}", skill_type_id));
+        localized(skill_type_id, &english)
+    };
+
+    let reprocessed_items: Vec<ReprocessedItem> = salvage_inputs
+        .iter()
+        .map(|salvage| ReprocessedItem {
+            type_id: salvage.type_id,
+            name: localized(salvage.type_id, &salvage.name),
+            quantity: salvage.quantity,
+            processing_skill: processing_skills.get(&salvage.type_id).map(|id| skill_name(*id)),
+            efficiency: item_efficiency(salvage),
+        })
+        .collect();
+
+    // Group/category labels for products
+    let classifications: HashMap<i32, TypeClassification> =
+        queries::get_type_classifications(&pool, &product_type_ids)
+            .await
+            .map_err(ApiError::Database)?
+            .into_iter()
+            .map(|c| (c.type_id, c))
+            .collect();
+
+    // Dogma attributes (tech level, rig size, ...) for products
+    let product_attributes: HashMap<i32, TypeAttributes> =
+        queries::get_type_attributes(&pool, &product_type_ids)
+            .await
+            .map_err(ApiError::Database)?
+            .into_iter()
+            .map(|a| (a.type_id, a))
+            .collect();
 
     // Calculate material outputs with prices
     let mut material_outputs = Vec::new();
@@ -153,13 +435,16 @@ pub async fn analyze_salvage(
             .and_then(|p| p.sell_price);
         let total_value = unit_price.unwrap_or(0.0) * material.quantity as f64;
         total_material_value += total_value;
+        let (price_age_minutes, price_status) = freshness(&all_prices, material.type_id);
 
         material_outputs.push(MaterialOutput {
             type_id: material.type_id,
-            name: material.name.clone(),
+            name: localized(material.type_id, &material.name),
             quantity: material.quantity,
             unit_price,
             total_value,
+            price_age_minutes,
+            price_status,
         });
     }
 
@@ -167,27 +452,68 @@ pub async fn analyze_salvage(
     let mut buildable_items = Vec::new();
 
     for bp_match in blueprint_matches {
+        let classification = classifications.get(&bp_match.product.type_id);
+        let category_name = classification.and_then(|c| c.category_name.clone());
+
+        // Apply product category filter
+        if !request.product_categories.is_empty() {
+            let in_category = category_name.as_ref().is_some_and(|name| {
+                request
+                    .product_categories
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(name))
+            });
+            if !in_category {
+                continue;
+            }
+        }
+
+        // Apply rig size and tech level filters
+        let attributes = product_attributes.get(&bp_match.product.type_id);
+        let rig_size = attributes.and_then(|a| a.rig_size_name());
+        if !request.rig_sizes.is_empty()
+            && !rig_size.is_some_and(|size| request.rig_sizes.iter().any(|wanted| wanted.eq_ignore_ascii_case(size)))
+        {
+            continue;
+        }
+        let tech_level = attributes.and_then(|a| a.tech_level);
+        if !request.tech_levels.is_empty()
+            && !tech_level.is_some_and(|level| request.tech_levels.contains(&level))
+        {
+            continue;
+        }
+
         // Get product price
         let product_price = all_prices
             .get(&bp_match.product.type_id)
             .and_then(|p| p.sell_price)
             .unwrap_or(0.0);
 
+        let (mut price_age_minutes, mut price_status) = freshness(&all_prices, bp_match.product.type_id);
+
+        // One run yields product_quantity units (e.g. 100 rounds of ammunition)
+        let product_quantity = bp_match.blueprint.product_quantity;
+        let product_value = product_price * product_quantity as f64;
+
         // Calculate cost of missing materials
         let mut missing_material_cost = 0.0;
         let mut missing_materials_output = Vec::new();
 
         for missing in &bp_match.missing_materials {
-            let unit_price = all_prices
+            let unit_price = buy_prices
                 .get(&missing.type_id)
                 .and_then(|p| p.sell_price)
                 .unwrap_or(0.0);
             let cost = unit_price * missing.missing as f64;
             missing_material_cost += cost;
 
+            let (age, status) = freshness(&buy_prices, missing.type_id);
+            price_age_minutes = price_age_minutes.max(age);
+            price_status = price_status.max(status);
+
             missing_materials_output.push(MissingMaterial {
                 type_id: missing.type_id,
-                name: missing.name.clone(),
+                name: localized(missing.type_id, &missing.name),
                 needed: missing.needed,
                 available: missing.available,
                 missing: missing.missing,
@@ -195,23 +521,55 @@ pub async fn analyze_salvage(
             });
         }
 
+        // An age shown next to a missing price would read as that price's age
+        if price_status == PriceStatus::Missing {
+            price_age_minutes = None;
+        }
+
+        // Manufacturing skills the character has not trained far enough
+        let missing_skills: Vec<MissingSkill> = blueprint_skills
+            .get(&bp_match.blueprint.blueprint_type_id)
+            .into_iter()
+            .flatten()
+            .filter(|skill| (trained_level(skill.skill_type_id) as i32) < skill.level)
+            .map(|skill| MissingSkill {
+                skill_type_id: skill.skill_type_id,
+                name: skill_name(skill.skill_type_id),
+                required_level: skill.level,
+                trained_level: trained_level(skill.skill_type_id) as i32,
+            })
+            .collect();
+
         // Industry cost (simplified - could be calculated based on system index)
         let industry_cost = 1000.0; // Placeholder
 
         let total_cost = missing_material_cost + industry_cost;
-        let profit = market_data::calculate_profit(product_price, total_cost, 0.0);
-        let profit_margin = market_data::calculate_profit_margin(profit, product_price);
+        let profit = market_data::calculate_profit(product_value, total_cost, 0.0);
+        let profit_margin = market_data::calculate_profit_margin(profit, product_value);
 
         buildable_items.push(BuildableItem {
             product_type_id: bp_match.product.type_id,
-            product_name: bp_match.product.name,
+            product_name: localized(bp_match.product.type_id, &bp_match.product.name),
+            group_name: classification.and_then(|c| c.group_name.clone()),
+            category_name,
+            tech_level,
+            meta_level: attributes.and_then(|a| a.meta_level),
+            rig_size: rig_size.map(str::to_string),
+            calibration: attributes.and_then(|a| a.calibration),
+            slot: attributes.and_then(|a| a.slot.clone()),
             match_percentage: bp_match.match_percentage,
             can_build: bp_match.match_percentage >= 100.0,
             estimated_profit: profit,
             profit_margin,
             product_price,
+            product_quantity,
+            product_value,
             material_cost: total_cost,
             missing_materials: missing_materials_output,
+            has_required_skills: use_skills.then_some(missing_skills.is_empty()),
+            missing_skills,
+            price_age_minutes,
+            price_status,
         });
     }
 
@@ -232,6 +590,78 @@ pub async fn analyze_salvage(
         total_material_value,
         buildable_items,
         reprocessing_efficiency_used: reprocessing_efficiency,
+        reprocessed_items,
         unknown_items,
+        sell_hub: request.sell_hub,
+        buy_hub: request.buy_hub,
+        warnings,
     }))
 }
+
+/// Re-price the types whose cached price at `location` is stale or missing from the
+/// location's price source, returning how many were updated, or a warning if the refresh failed
+async fn refresh_stale_prices(
+    pool: &DbPool,
+    sources: &PriceSources,
+    location: MarketLocation,
+    type_ids: &[i32],
+    prices: &HashMap<i32, MarketPrice>,
+    max_age_minutes: i64,
+) -> Result<usize, String> {
+    let now = chrono::Utc::now();
+    let mut stale: Vec<i32> = type_ids
+        .iter()
+        .copied()
+        .filter(|id| market_data::price_status(prices.get(id), max_age_minutes, now) != PriceStatus::Fresh)
+        .collect();
+    stale.sort_unstable();
+    stale.dedup();
+
+    if stale.is_empty() {
+        return Ok(0);
+    }
+
+    let refreshed = async {
+        let source = sources.for_location(location)?;
+        tracing::info!("Refreshing {} stale prices at {} from {}", stale.len(), location, source.name());
+        price_refresh::refresh_location(pool, source.as_ref(), location, &stale).await
+    };
+    refreshed
+        .await
+        .map(|refreshed| refreshed.updated_count)
+        .map_err(|e| format!("Could not refresh {} stale prices at {}: {}", stale.len(), location, e))
+}
+
+/// Warnings for the stale and missing prices among `type_ids` at `location`
+fn price_warnings(
+    location: MarketLocation,
+    type_ids: &[i32],
+    prices: &HashMap<i32, MarketPrice>,
+    max_age_minutes: i64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<String> {
+    let mut unique = type_ids.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+
+    let count = |status| {
+        unique
+            .iter()
+            .filter(|id| market_data::price_status(prices.get(id), max_age_minutes, now) == status)
+            .count()
+    };
+
+    let mut warnings = Vec::new();
+    let stale = count(PriceStatus::Stale);
+    if stale > 0 {
+        warnings.push(format!(
+            "{} prices at {} are older than {} minutes",
+            stale, location, max_age_minutes
+        ));
+    }
+    let missing = count(PriceStatus::Missing);
+    if missing > 0 {
+        warnings.push(format!("{} prices at {} are missing and count as 0", missing, location));
+    }
+    warnings
+}
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{models::*, queries, DbPool},
+    error::{ApiError, ApiResult},
+    services::market_groups::{self, MarketGroupNode},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct MarketGroupTreeQuery {
+    /// Return only the subtree below this group
+    pub root: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketGroupTypesQuery {
+    /// Include types from all descendant groups
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypeDetail {
+    #[serde(flatten)]
+    pub eve_type: EveType,
+    pub group_name: Option<String>,
+    pub category_name: Option<String>,
+    /// Dogma attributes; None for types without meta/tech level, rig size, calibration or slot
+    pub attributes: Option<TypeAttributes>,
+}
+
+/// Get a type with its group, category and dogma attributes
+pub async fn get_type(
+    State(pool): State<DbPool>,
+    Path(type_id): Path<i32>,
+) -> ApiResult<Json<TypeDetail>> {
+    let eve_type = queries::get_type_by_id(&pool, type_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Type {} not found", type_id)))?;
+
+    let classification = queries::get_type_classifications(&pool, &[type_id])
+        .await
+        .map_err(ApiError::Database)?
+        .pop();
+    let attributes = queries::get_type_attributes(&pool, &[type_id])
+        .await
+        .map_err(ApiError::Database)?
+        .pop();
+
+    Ok(Json(TypeDetail {
+        eve_type,
+        group_name: classification.as_ref().and_then(|c| c.group_name.clone()),
+        category_name: classification.and_then(|c| c.category_name),
+        attributes,
+    }))
+}
+
+/// List all item categories (Ship, Module, Charge, ...)
+pub async fn list_categories(State(pool): State<DbPool>) -> ApiResult<Json<Vec<EveCategory>>> {
+    let categories = queries::get_categories(&pool)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(categories))
+}
+
+/// List the groups within a category
+pub async fn list_category_groups(
+    State(pool): State<DbPool>,
+    Path(category_id): Path<i32>,
+) -> ApiResult<Json<Vec<EveGroup>>> {
+    let groups = queries::get_groups_for_category(&pool, category_id)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(groups))
+}
+
+/// Get the market group tree (whole market, or the subtree below `?root=`)
+pub async fn get_market_group_tree(
+    State(pool): State<DbPool>,
+    Query(query): Query<MarketGroupTreeQuery>,
+) -> ApiResult<Json<Vec<MarketGroupNode>>> {
+    let tree = market_groups::get_market_group_tree(&pool, query.root).await?;
+
+    Ok(Json(tree))
+}
+
+/// List the types in a market group (`?recursive=true` to include subgroups)
+pub async fn list_market_group_types(
+    State(pool): State<DbPool>,
+    Path(market_group_id): Path<i32>,
+    Query(query): Query<MarketGroupTypesQuery>,
+) -> ApiResult<Json<Vec<EveType>>> {
+    queries::get_market_group(&pool, market_group_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Market group {} not found", market_group_id)))?;
+
+    let types = queries::get_types_in_market_group(&pool, market_group_id, query.recursive)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(types))
+}
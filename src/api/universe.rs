@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{models::*, queries, DbPool},
+    error::{ApiError, ApiResult},
+};
+
+/// Maximum number of systems returned by a name search
+const SYSTEM_SEARCH_LIMIT: i64 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct SystemSearchQuery {
+    /// Name prefix, case-insensitive (e.g. "jit")
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SolarSystemDetail {
+    #[serde(flatten)]
+    pub system: SolarSystem,
+    pub constellation_name: Option<String>,
+    pub region_name: Option<String>,
+    /// Systems one stargate jump away
+    pub adjacent_systems: Vec<SolarSystem>,
+    pub stations: Vec<Station>,
+}
+
+/// List all regions
+pub async fn list_regions(State(pool): State<DbPool>) -> ApiResult<Json<Vec<Region>>> {
+    let regions = queries::get_regions(&pool)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(regions))
+}
+
+/// List the solar systems in a region
+pub async fn list_region_systems(
+    State(pool): State<DbPool>,
+    Path(region_id): Path<i32>,
+) -> ApiResult<Json<Vec<SolarSystem>>> {
+    queries::get_region(&pool, region_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Region {} not found", region_id)))?;
+
+    let systems = queries::get_systems_in_region(&pool, region_id)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(systems))
+}
+
+/// Search solar systems by name prefix (`?name=`)
+pub async fn search_systems(
+    State(pool): State<DbPool>,
+    Query(query): Query<SystemSearchQuery>,
+) -> ApiResult<Json<Vec<SolarSystem>>> {
+    if query.name.trim().is_empty() {
+        return Err(ApiError::InvalidInput("name must not be empty".to_string()));
+    }
+
+    let systems = queries::search_solar_systems(&pool, query.name.trim(), SYSTEM_SEARCH_LIMIT)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(systems))
+}
+
+/// Get a solar system with its location, stargate neighbours and NPC stations
+pub async fn get_system(
+    State(pool): State<DbPool>,
+    Path(solar_system_id): Path<i32>,
+) -> ApiResult<Json<SolarSystemDetail>> {
+    let system = queries::get_solar_system(&pool, solar_system_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Solar system {} not found", solar_system_id)))?;
+
+    let constellation = queries::get_constellation(&pool, system.constellation_id)
+        .await
+        .map_err(ApiError::Database)?;
+    let region = queries::get_region(&pool, system.region_id)
+        .await
+        .map_err(ApiError::Database)?;
+
+    let adjacent_systems = queries::get_adjacent_systems(&pool, solar_system_id)
+        .await
+        .map_err(ApiError::Database)?;
+    let stations = queries::get_stations_in_system(&pool, solar_system_id)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(Json(SolarSystemDetail {
+        system,
+        constellation_name: constellation.map(|c| c.name),
+        region_name: region.map(|r| r.name),
+        adjacent_systems,
+        stations,
+    }))
+}
+
+/// Get an NPC station
+pub async fn get_station(
+    State(pool): State<DbPool>,
+    Path(station_id): Path<i32>,
+) -> ApiResult<Json<Station>> {
+    let station = queries::get_station(&pool, station_id)
+        .await
+        .map_err(ApiError::Database)?
+        .ok_or_else(|| ApiError::NotFound(format!("Station {} not found", station_id)))?;
+
+    Ok(Json(station))
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use sqlx::query_builder::Separated;
+use sqlx::sqlite::{Sqlite, SqliteConnection};
+use sqlx::QueryBuilder;
+use std::collections::HashSet;
+
+use crate::progress::Progress;
+
+/// Rows per multi-row INSERT (kept well below SQLite's bound parameter limit)
+pub const BATCH_SIZE: usize = 500;
+
+/// Insert rows with multi-row `INSERT ... VALUES (...), (...)` statements
+///
+/// `insert` is the statement head (e.g. `INSERT INTO t (a, b)`), `on_conflict` is appended
+/// after the VALUES list and may be empty. `bind` pushes one row's values in column order.
+pub async fn insert_batched<'a, T>(
+    conn: &mut SqliteConnection,
+    insert: &str,
+    on_conflict: &str,
+    rows: &'a [T],
+    progress: &mut Progress,
+    mut bind: impl FnMut(&mut Separated<'_, 'a, Sqlite, &'static str>, &'a T),
+) -> Result<u64> {
+    let mut affected = 0;
+
+    for chunk in rows.chunks(BATCH_SIZE) {
+        let mut builder = QueryBuilder::<Sqlite>::new(insert);
+        builder.push(" ");
+        builder.push_values(chunk, |mut row_builder, row| bind(&mut row_builder, row));
+        if !on_conflict.is_empty() {
+            builder.push(" ");
+            builder.push(on_conflict);
+        }
+
+        affected += builder.build().execute(&mut *conn).await?.rows_affected();
+        progress.advance(chunk.len());
+    }
+
+    Ok(affected)
+}
+
+/// Execute a statement once per batch of ids
+///
+/// Every `{ids}` placeholder in `sql` is replaced by the batch's bound id list,
+/// e.g. `DELETE FROM t WHERE id IN ({ids})`.
+pub async fn execute_for_ids(conn: &mut SqliteConnection, sql: &str, ids: &[i32]) -> Result<u64> {
+    let parts: Vec<&str> = sql.split("{ids}").collect();
+    let mut affected = 0;
+
+    for chunk in ids.chunks(BATCH_SIZE) {
+        let mut builder = QueryBuilder::<Sqlite>::new(parts[0]);
+        for part in &parts[1..] {
+            let mut separated = builder.separated(", ");
+            for id in chunk {
+                separated.push_bind(*id);
+            }
+            builder.push(*part);
+        }
+
+        affected += builder.build().execute(&mut *conn).await?.rows_affected();
+    }
+
+    Ok(affected)
+}
+
+/// Load every type_id currently in eve_types, used to skip rows that would violate foreign keys
+pub async fn load_known_type_ids(conn: &mut SqliteConnection) -> Result<HashSet<i32>> {
+    let type_ids: Vec<i32> = sqlx::query_scalar("SELECT type_id FROM eve_types")
+        .fetch_all(conn)
+        .await?;
+
+    Ok(type_ids.into_iter().collect())
+}
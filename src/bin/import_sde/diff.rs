@@ -0,0 +1,133 @@
+use anyhow::Result;
+use sqlx::sqlite::SqliteConnection;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::db::insert_batched;
+use crate::progress::Progress;
+
+/// Differences between what a previous SDE import stored and the SDE being imported
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    pub added: Vec<i32>,
+    pub changed: Vec<i32>,
+    pub removed: Vec<i32>,
+}
+
+impl Diff {
+    /// Compare stored records (from earlier imports) with incoming SDE records by id
+    pub fn compute<V: PartialEq>(current: &HashMap<i32, V>, incoming: &HashMap<i32, V>) -> Self {
+        let mut diff = Diff::default();
+
+        for (id, record) in incoming {
+            match current.get(id) {
+                None => diff.added.push(*id),
+                Some(existing) if existing != record => diff.changed.push(*id),
+                Some(_) => {}
+            }
+        }
+
+        diff.removed = current
+            .keys()
+            .filter(|id| !incoming.contains_key(id))
+            .copied()
+            .collect();
+
+        diff.added.sort_unstable();
+        diff.changed.sort_unstable();
+        diff.removed.sort_unstable();
+        diff
+    }
+
+    /// Classify one incoming record against its stored counterpart, for streamed imports
+    ///
+    /// Returns true if the record needs to be written. Removals are not tracked here;
+    /// set `removed` once the whole input has been seen.
+    pub fn classify<V: PartialEq>(&mut self, id: i32, current: Option<&V>, incoming: &V) -> bool {
+        match current {
+            None => {
+                self.added.push(id);
+                true
+            }
+            Some(existing) if existing != incoming => {
+                self.changed.push(id);
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Ids that need to be written (added or changed)
+    pub fn upserts(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self.added.iter().chain(&self.changed).copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Persist the diff to sde_import_changes for the run
+    pub async fn record(&self, conn: &mut SqliteConnection, run_id: i64, entity: &str) -> Result<()> {
+        let rows: Vec<(i32, &str)> = self.added.iter().map(|id| (*id, "added"))
+            .chain(self.changed.iter().map(|id| (*id, "changed")))
+            .chain(self.removed.iter().map(|id| (*id, "removed")))
+            .collect();
+
+        let mut progress = Progress::new("changes", rows.len());
+        insert_batched(
+            conn,
+            "INSERT INTO sde_import_changes (run_id, entity, entity_id, change)",
+            "",
+            &rows,
+            &mut progress,
+            |b, (entity_id, change)| {
+                b.push_bind(run_id)
+                    .push_bind(entity.to_string())
+                    .push_bind(*entity_id)
+                    .push_bind(*change);
+            },
+        )
+        .await?;
+        progress.finish();
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} changed, {} removed",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_added_changed_removed() {
+        let current = HashMap::from([(1, "Tritanium"), (2, "Pyerite"), (3, "Mexallon")]);
+        let incoming = HashMap::from([(1, "Tritanium"), (2, "Pyerite II"), (4, "Isogen")]);
+
+        let diff = Diff::compute(&current, &incoming);
+        assert_eq!(diff.added, vec![4]);
+        assert_eq!(diff.changed, vec![2]);
+        assert_eq!(diff.removed, vec![3]);
+        assert_eq!(diff.upserts(), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_diff_first_import_adds_everything() {
+        let current: HashMap<i32, i32> = HashMap::new();
+        let incoming = HashMap::from([(2, 20), (1, 10)]);
+
+        let diff = Diff::compute(&current, &incoming);
+        assert_eq!(diff.added, vec![1, 2]);
+        assert!(diff.changed.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+}
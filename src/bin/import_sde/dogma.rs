@@ -0,0 +1,174 @@
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::sqlite::SqliteConnection;
+use std::collections::HashMap;
+
+use crate::db::{execute_for_ids, insert_batched, load_known_type_ids};
+use crate::diff::Diff;
+use crate::progress::Progress;
+use crate::sde::stream_fsd;
+use crate::ImportContext;
+
+#[derive(Debug, Deserialize)]
+pub struct TypeDogma {
+    #[serde(rename = "dogmaAttributes")]
+    pub dogma_attributes: Option<Vec<DogmaAttribute>>,
+    #[serde(rename = "dogmaEffects")]
+    pub dogma_effects: Option<Vec<DogmaEffect>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DogmaAttribute {
+    #[serde(rename = "attributeID")]
+    pub attribute_id: i32,
+    pub value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DogmaEffect {
+    #[serde(rename = "effectID")]
+    pub effect_id: i32,
+}
+
+impl TypeDogma {
+    /// Value of a dogma attribute, if the type has it
+    pub fn attribute(&self, attribute_id: i32) -> Option<f64> {
+        self.dogma_attributes
+            .iter()
+            .flatten()
+            .find(|a| a.attribute_id == attribute_id)
+            .map(|a| a.value)
+    }
+}
+
+/// Dogma attribute IDs from dogmaAttributes.yaml
+const META_LEVEL_ATTRIBUTE: i32 = 633; // metaLevelOld
+const TECH_LEVEL_ATTRIBUTE: i32 = 422; // techLevel
+const RIG_SIZE_ATTRIBUTE: i32 = 1547; // rigSize
+const CALIBRATION_ATTRIBUTE: i32 = 1153; // upgradeCost
+
+/// Dogma effects that fit a module into a slot, with the slot name stored in type_attributes
+const SLOT_EFFECTS: [(i32, &str); 5] = [
+    (12, "high"), // hiPower
+    (13, "medium"), // medPower
+    (11, "low"), // loPower
+    (2663, "rig"), // rigSlot
+    (3772, "subsystem"), // subSystem
+];
+
+/// Dogma-derived fields stored in type_attributes, compared between imports
+#[derive(Debug, PartialEq)]
+struct TypeAttributeRecord {
+    meta_level: Option<i32>,
+    tech_level: Option<i32>,
+    rig_size: Option<i32>,
+    calibration: Option<i32>,
+    slot: Option<String>,
+}
+
+impl TypeAttributeRecord {
+    /// None for types with none of the stored attributes
+    fn from_dogma(dogma: &TypeDogma) -> Option<Self> {
+        let integer = |attribute_id| dogma.attribute(attribute_id).map(|value| value as i32);
+        let slot = dogma.dogma_effects.iter().flatten().find_map(|effect| {
+            SLOT_EFFECTS
+                .iter()
+                .find(|(effect_id, _)| *effect_id == effect.effect_id)
+                .map(|(_, slot)| slot.to_string())
+        });
+
+        let record = Self {
+            meta_level: integer(META_LEVEL_ATTRIBUTE),
+            tech_level: integer(TECH_LEVEL_ATTRIBUTE),
+            rig_size: integer(RIG_SIZE_ATTRIBUTE),
+            calibration: integer(CALIBRATION_ATTRIBUTE),
+            slot,
+        };
+
+        let empty = record.meta_level.is_none()
+            && record.tech_level.is_none()
+            && record.rig_size.is_none()
+            && record.calibration.is_none()
+            && record.slot.is_none();
+        (!empty).then_some(record)
+    }
+}
+
+pub async fn import_type_attributes(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing type attributes...");
+
+    let Some(mut entries) = stream_fsd::<TypeDogma>(ctx.sde, "typeDogma.yaml") else {
+        return Ok(0);
+    };
+
+    let known_types = load_known_type_ids(conn).await?;
+
+    let mut incoming: HashMap<i32, TypeAttributeRecord> = HashMap::new();
+    let mut skipped = 0;
+
+    while let Some(entry) = entries.recv().await {
+        let (type_id, dogma) = entry?;
+
+        let Some(record) = TypeAttributeRecord::from_dogma(&dogma) else {
+            continue;
+        };
+
+        if !known_types.contains(&type_id) {
+            skipped += 1;
+            continue;
+        }
+
+        incoming.insert(type_id, record);
+    }
+
+    let current: HashMap<i32, TypeAttributeRecord> = sqlx::query_as::<_, (i32, Option<i32>, Option<i32>, Option<i32>, Option<i32>, Option<String>)>(
+        "SELECT type_id, meta_level, tech_level, rig_size, calibration, slot FROM type_attributes"
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|(type_id, meta_level, tech_level, rig_size, calibration, slot)| {
+        (type_id, TypeAttributeRecord { meta_level, tech_level, rig_size, calibration, slot })
+    })
+    .collect();
+
+    let diff = Diff::compute(&current, &incoming);
+    println!("Type attribute changes: {}", diff);
+
+    let rows: Vec<(i32, &TypeAttributeRecord)> = diff.upserts().iter().map(|id| (*id, &incoming[id])).collect();
+
+    let mut progress = Progress::new("type attributes", rows.len());
+    let imported = insert_batched(
+        conn,
+        "INSERT INTO type_attributes (type_id, meta_level, tech_level, rig_size, calibration, slot)",
+        "ON CONFLICT(type_id) DO UPDATE SET
+            meta_level = excluded.meta_level,
+            tech_level = excluded.tech_level,
+            rig_size = excluded.rig_size,
+            calibration = excluded.calibration,
+            slot = excluded.slot",
+        &rows,
+        &mut progress,
+        |b, (type_id, record)| {
+            b.push_bind(*type_id)
+                .push_bind(record.meta_level)
+                .push_bind(record.tech_level)
+                .push_bind(record.rig_size)
+                .push_bind(record.calibration)
+                .push_bind(record.slot.as_deref());
+        },
+    )
+    .await?;
+    progress.finish();
+
+    execute_for_ids(conn, "DELETE FROM type_attributes WHERE type_id IN ({ids})", &diff.removed).await?;
+    diff.record(conn, ctx.run_id, "type_attributes").await?;
+
+    println!("✓ Imported attributes for {} types", imported);
+    if skipped > 0 {
+        println!("⚠️  Skipped {} types not present in eve_types", skipped);
+    }
+    println!();
+
+    Ok(imported + diff.removed.len() as u64)
+}
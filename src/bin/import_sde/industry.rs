@@ -0,0 +1,722 @@
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::sqlite::SqliteConnection;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::db::{execute_for_ids, insert_batched, load_known_type_ids};
+use crate::diff::Diff;
+use crate::dogma::TypeDogma;
+use crate::progress::Progress;
+use crate::sde::{load_fsd, stream_fsd};
+use crate::ImportContext;
+
+#[derive(Debug, Deserialize)]
+struct BlueprintEntry {
+    activities: BlueprintActivities,
+    #[serde(rename = "maxProductionLimit")]
+    max_production_limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlueprintActivities {
+    manufacturing: Option<BlueprintActivity>,
+    invention: Option<BlueprintActivity>,
+    copying: Option<BlueprintActivity>,
+    research_material: Option<BlueprintActivity>,
+    research_time: Option<BlueprintActivity>,
+}
+
+impl BlueprintActivities {
+    /// Activities stored in blueprint_activities, keyed by their SDE name
+    fn other_activities(self) -> impl Iterator<Item = (&'static str, BlueprintActivity)> {
+        [
+            ("invention", self.invention),
+            ("copying", self.copying),
+            ("research_material", self.research_material),
+            ("research_time", self.research_time),
+        ]
+        .into_iter()
+        .filter_map(|(activity, data)| data.map(|data| (activity, data)))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BlueprintActivity {
+    materials: Option<Vec<Material>>,
+    products: Option<Vec<Product>>,
+    skills: Option<Vec<Skill>>,
+    time: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Material {
+    #[serde(rename = "typeID")]
+    type_id: i32,
+    quantity: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Product {
+    #[serde(rename = "typeID")]
+    type_id: i32,
+    quantity: i32,
+    probability: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Skill {
+    #[serde(rename = "typeID")]
+    type_id: i32,
+    level: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TypeMaterials {
+    materials: Option<Vec<TypeMaterial>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TypeMaterial {
+    #[serde(rename = "materialTypeID")]
+    material_type_id: i32,
+    quantity: i32,
+}
+
+/// Dogma attribute holding the type_id of the skill that improves a type's reprocessing yield
+const REPROCESSING_SKILL_ATTRIBUTE: i32 = 790;
+
+/// Blueprint fields owned by the SDE, compared between imports
+#[derive(Debug, PartialEq)]
+struct BlueprintRecord {
+    product_type_id: i32,
+    product_quantity: i32,
+    manufacturing_time: Option<i32>,
+    max_production_limit: Option<i32>,
+    /// (material_type_id, quantity), sorted by material
+    materials: Vec<(i32, i32)>,
+    /// (skill_type_id, level), sorted by skill
+    skills: Vec<(i32, i32)>,
+}
+
+/// One invention, copying or research activity of a blueprint, compared between imports
+#[derive(Debug, Default, PartialEq)]
+struct ActivityRecord {
+    time: Option<i32>,
+    /// (material_type_id, quantity), sorted by material
+    materials: Vec<(i32, i32)>,
+    /// (product_type_id, quantity, probability), sorted by product
+    products: Vec<(i32, i32, Option<f64>)>,
+    /// (skill_type_id, level), sorted by skill
+    skills: Vec<(i32, i32)>,
+}
+
+impl ActivityRecord {
+    fn from_sde(activity: BlueprintActivity, known_types: &HashSet<i32>) -> Self {
+        let mut materials: Vec<(i32, i32)> = activity.materials.iter().flatten()
+            .filter(|m| m.quantity > 0 && known_types.contains(&m.type_id))
+            .map(|m| (m.type_id, m.quantity))
+            .collect();
+        materials.sort_unstable();
+        materials.dedup_by_key(|(material_type_id, _)| *material_type_id);
+
+        let mut products: Vec<(i32, i32, Option<f64>)> = activity.products.iter().flatten()
+            .filter(|p| p.quantity > 0 && known_types.contains(&p.type_id))
+            .map(|p| (p.type_id, p.quantity, p.probability))
+            .collect();
+        products.sort_unstable_by_key(|(product_type_id, _, _)| *product_type_id);
+        products.dedup_by_key(|(product_type_id, _, _)| *product_type_id);
+
+        let mut skills: Vec<(i32, i32)> = activity.skills.iter().flatten()
+            .filter(|s| known_types.contains(&s.type_id))
+            .map(|s| (s.type_id, s.level))
+            .collect();
+        skills.sort_unstable();
+        skills.dedup_by_key(|(skill_type_id, _)| *skill_type_id);
+
+        Self {
+            time: activity.time,
+            materials,
+            products,
+            skills,
+        }
+    }
+}
+
+pub async fn import_type_materials(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing reprocessing yields...");
+
+    let Some(type_materials) = load_fsd::<HashMap<i32, TypeMaterials>>(ctx.sde, "typeMaterials.yaml")? else {
+        return Ok(0);
+    };
+
+    println!("Found {} reprocessable types", type_materials.len());
+
+    // Source and material types must already exist in eve_types (foreign keys)
+    let known_types = load_known_type_ids(conn).await?;
+
+    // (material_type_id, quantity) per source type, sorted by material
+    let mut incoming: HashMap<i32, Vec<(i32, i32)>> = HashMap::new();
+    let mut skipped = 0;
+
+    for (source_type_id, entry) in type_materials {
+        if !known_types.contains(&source_type_id) {
+            skipped += 1;
+            continue;
+        }
+
+        let mut yields: Vec<(i32, i32)> = entry.materials.iter().flatten()
+            .filter(|m| m.quantity > 0 && known_types.contains(&m.material_type_id))
+            .map(|m| (m.material_type_id, m.quantity))
+            .collect();
+        yields.sort_unstable();
+        yields.dedup_by_key(|(material_type_id, _)| *material_type_id);
+
+        if !yields.is_empty() {
+            incoming.insert(source_type_id, yields);
+        }
+    }
+
+    // Compare against what earlier SDE imports stored
+    let mut current: HashMap<i32, Vec<(i32, i32)>> = HashMap::new();
+    let stored: Vec<(i32, i32, i32)> = sqlx::query_as(
+        "SELECT source_type_id, material_type_id, quantity FROM material_reprocessing
+         WHERE from_sde = 1
+         ORDER BY source_type_id, material_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (source_type_id, material_type_id, quantity) in stored {
+        current.entry(source_type_id).or_default().push((material_type_id, quantity));
+    }
+
+    let diff = Diff::compute(&current, &incoming);
+    println!("Reprocessing changes: {}", diff);
+
+    // Replace the full yield list of every added/changed type (including seeded yields),
+    // and drop yields of types that no longer reprocess
+    let upserts = diff.upserts();
+    execute_for_ids(conn, "DELETE FROM material_reprocessing WHERE source_type_id IN ({ids})", &upserts).await?;
+    execute_for_ids(conn, "DELETE FROM material_reprocessing WHERE source_type_id IN ({ids})", &diff.removed).await?;
+
+    let yields: Vec<(i32, i32, i32)> = upserts
+        .iter()
+        .flat_map(|source_type_id| {
+            incoming[source_type_id]
+                .iter()
+                .map(|(material_type_id, quantity)| (*source_type_id, *material_type_id, *quantity))
+        })
+        .collect();
+
+    let mut progress = Progress::new("yields", yields.len());
+    let imported = insert_batched(
+        conn,
+        "INSERT INTO material_reprocessing (source_type_id, material_type_id, quantity, from_sde)",
+        "",
+        &yields,
+        &mut progress,
+        |b, (source_type_id, material_type_id, quantity)| {
+            b.push_bind(*source_type_id)
+                .push_bind(*material_type_id)
+                .push_bind(*quantity)
+                .push_bind(true);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    diff.record(conn, ctx.run_id, "reprocessing").await?;
+
+    println!("✓ Imported reprocessing yields for {} types ({} yields)", upserts.len(), imported);
+    if skipped > 0 {
+        println!("⚠️  Skipped {} types not present in eve_types", skipped);
+    }
+    println!();
+
+    Ok(imported)
+}
+
+pub async fn import_reprocessing_skills(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing reprocessing skills...");
+
+    // typeDogma.yaml covers every type's attributes; stream it and keep only the skill ids
+    let Some(mut entries) = stream_fsd::<TypeDogma>(ctx.sde, "typeDogma.yaml") else {
+        return Ok(0);
+    };
+
+    let known_types = load_known_type_ids(conn).await?;
+
+    let mut incoming: HashMap<i32, i32> = HashMap::new();
+    let mut skipped = 0;
+
+    while let Some(entry) = entries.recv().await {
+        let (type_id, dogma) = entry?;
+
+        let Some(skill_type_id) = dogma.attribute(REPROCESSING_SKILL_ATTRIBUTE).map(|value| value as i32) else {
+            continue;
+        };
+
+        if !known_types.contains(&type_id) || !known_types.contains(&skill_type_id) {
+            skipped += 1;
+            continue;
+        }
+
+        incoming.insert(type_id, skill_type_id);
+    }
+
+    let current: HashMap<i32, i32> =
+        sqlx::query_as::<_, (i32, i32)>("SELECT source_type_id, skill_type_id FROM reprocessing_skills")
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+
+    let diff = Diff::compute(&current, &incoming);
+    println!("Reprocessing skill changes: {}", diff);
+
+    let rows: Vec<(i32, i32)> = diff.upserts().iter().map(|id| (*id, incoming[id])).collect();
+
+    let mut progress = Progress::new("reprocessing skills", rows.len());
+    let imported = insert_batched(
+        conn,
+        "INSERT INTO reprocessing_skills (source_type_id, skill_type_id)",
+        "ON CONFLICT(source_type_id) DO UPDATE SET skill_type_id = excluded.skill_type_id",
+        &rows,
+        &mut progress,
+        |b, (source_type_id, skill_type_id)| {
+            b.push_bind(*source_type_id).push_bind(*skill_type_id);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    execute_for_ids(conn, "DELETE FROM reprocessing_skills WHERE source_type_id IN ({ids})", &diff.removed).await?;
+    diff.record(conn, ctx.run_id, "reprocessing_skill").await?;
+
+    println!("✓ Imported reprocessing skills for {} types", imported);
+    if skipped > 0 {
+        println!("⚠️  Skipped {} types or skills not present in eve_types", skipped);
+    }
+    println!();
+
+    Ok(imported + diff.removed.len() as u64)
+}
+
+pub async fn import_blueprints(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing blueprints...");
+
+    println!("Parsing blueprints.yaml (this may take a while)...");
+    let Some(blueprints) = load_fsd::<HashMap<i32, BlueprintEntry>>(ctx.sde, "blueprints.yaml")? else {
+        return Ok(0);
+    };
+
+    println!("Found {} blueprint definitions", blueprints.len());
+
+    // Every blueprint, product and material must already exist in eve_types (foreign keys)
+    let known_types = load_known_type_ids(conn).await?;
+
+    let mut incoming: HashMap<i32, BlueprintRecord> = HashMap::new();
+    let mut skipped = 0;
+
+    for (blueprint_type_id, blueprint) in blueprints {
+        let Some(manufacturing) = blueprint.activities.manufacturing else {
+            continue;
+        };
+
+        // Use first product (manufacturing blueprints only ever produce one)
+        let Some(product) = manufacturing
+            .products
+            .iter()
+            .flatten()
+            .find(|p| p.quantity > 0)
+        else {
+            continue;
+        };
+
+        if !known_types.contains(&blueprint_type_id) || !known_types.contains(&product.type_id) {
+            skipped += 1;
+            continue;
+        }
+
+        let mut materials: Vec<(i32, i32)> = manufacturing.materials.iter().flatten()
+            .filter(|m| m.quantity > 0 && known_types.contains(&m.type_id))
+            .map(|m| (m.type_id, m.quantity))
+            .collect();
+        materials.sort_unstable();
+        materials.dedup_by_key(|(material_type_id, _)| *material_type_id);
+
+        let mut skills: Vec<(i32, i32)> = manufacturing.skills.iter().flatten()
+            .filter(|s| known_types.contains(&s.type_id))
+            .map(|s| (s.type_id, s.level))
+            .collect();
+        skills.sort_unstable();
+        skills.dedup_by_key(|(skill_type_id, _)| *skill_type_id);
+
+        incoming.insert(blueprint_type_id, BlueprintRecord {
+            product_type_id: product.type_id,
+            product_quantity: product.quantity,
+            manufacturing_time: manufacturing.time,
+            max_production_limit: blueprint.max_production_limit,
+            materials,
+            skills,
+        });
+    }
+
+    // Compare against what earlier SDE imports stored
+    let mut current: HashMap<i32, BlueprintRecord> = sqlx::query_as::<_, (i32, i32, i32, Option<i32>, Option<i32>)>(
+        "SELECT blueprint_type_id, product_type_id, product_quantity, manufacturing_time, max_production_limit
+         FROM blueprints WHERE from_sde = 1"
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|(blueprint_type_id, product_type_id, product_quantity, manufacturing_time, max_production_limit)| {
+        (blueprint_type_id, BlueprintRecord {
+            product_type_id,
+            product_quantity,
+            manufacturing_time,
+            max_production_limit,
+            materials: Vec::new(),
+            skills: Vec::new(),
+        })
+    })
+    .collect();
+
+    let stored_materials: Vec<(i32, i32, i32)> = sqlx::query_as(
+        "SELECT blueprint_type_id, material_type_id, quantity FROM blueprint_materials
+         ORDER BY blueprint_type_id, material_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (blueprint_type_id, material_type_id, quantity) in stored_materials {
+        if let Some(record) = current.get_mut(&blueprint_type_id) {
+            record.materials.push((material_type_id, quantity));
+        }
+    }
+
+    let stored_skills: Vec<(i32, i32, i32)> = sqlx::query_as(
+        "SELECT blueprint_type_id, skill_type_id, level FROM blueprint_skills
+         ORDER BY blueprint_type_id, skill_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (blueprint_type_id, skill_type_id, level) in stored_skills {
+        if let Some(record) = current.get_mut(&blueprint_type_id) {
+            record.skills.push((skill_type_id, level));
+        }
+    }
+
+    let diff = Diff::compute(&current, &incoming);
+    println!("Blueprint changes: {}", diff);
+
+    let upserts = diff.upserts();
+    let blueprint_rows: Vec<(i32, &BlueprintRecord)> =
+        upserts.iter().map(|id| (*id, &incoming[id])).collect();
+
+    let mut progress = Progress::new("blueprints", blueprint_rows.len());
+    insert_batched(
+        conn,
+        "INSERT INTO blueprints
+         (blueprint_type_id, product_type_id, product_quantity, manufacturing_time, max_production_limit, from_sde)",
+        "ON CONFLICT(blueprint_type_id) DO UPDATE SET
+            product_type_id = excluded.product_type_id,
+            product_quantity = excluded.product_quantity,
+            manufacturing_time = excluded.manufacturing_time,
+            max_production_limit = excluded.max_production_limit,
+            from_sde = 1",
+        &blueprint_rows,
+        &mut progress,
+        |b, (blueprint_type_id, record)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(record.product_type_id)
+                .push_bind(record.product_quantity)
+                .push_bind(record.manufacturing_time)
+                .push_bind(record.max_production_limit)
+                .push_bind(true);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    // SDE is authoritative: replace any seeded material and skill lists for these blueprints
+    execute_for_ids(conn, "DELETE FROM blueprint_materials WHERE blueprint_type_id IN ({ids})", &upserts).await?;
+    execute_for_ids(conn, "DELETE FROM blueprint_skills WHERE blueprint_type_id IN ({ids})", &upserts).await?;
+
+    let material_rows: Vec<(i32, i32, i32)> = blueprint_rows
+        .iter()
+        .flat_map(|(blueprint_type_id, record)| {
+            record.materials
+                .iter()
+                .map(|(material_type_id, quantity)| (*blueprint_type_id, *material_type_id, *quantity))
+        })
+        .collect();
+
+    let mut progress = Progress::new("blueprint materials", material_rows.len());
+    let material_count = insert_batched(
+        conn,
+        "INSERT INTO blueprint_materials (blueprint_type_id, material_type_id, quantity)",
+        "",
+        &material_rows,
+        &mut progress,
+        |b, (blueprint_type_id, material_type_id, quantity)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(*material_type_id)
+                .push_bind(*quantity);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    let skill_rows: Vec<(i32, i32, i32)> = blueprint_rows
+        .iter()
+        .flat_map(|(blueprint_type_id, record)| {
+            record.skills
+                .iter()
+                .map(|(skill_type_id, level)| (*blueprint_type_id, *skill_type_id, *level))
+        })
+        .collect();
+
+    let skill_count = insert_batched(
+        conn,
+        "INSERT INTO blueprint_skills (blueprint_type_id, skill_type_id, level)",
+        "",
+        &skill_rows,
+        &mut Progress::silent(),
+        |b, (blueprint_type_id, skill_type_id, level)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(*skill_type_id)
+                .push_bind(*level);
+        },
+    )
+    .await?;
+
+    // Blueprints dropped from the SDE
+    execute_for_ids(conn, "DELETE FROM blueprint_materials WHERE blueprint_type_id IN ({ids})", &diff.removed).await?;
+    execute_for_ids(conn, "DELETE FROM blueprint_skills WHERE blueprint_type_id IN ({ids})", &diff.removed).await?;
+    execute_for_ids(conn, "DELETE FROM blueprints WHERE blueprint_type_id IN ({ids})", &diff.removed).await?;
+
+    diff.record(conn, ctx.run_id, "blueprint").await?;
+
+    println!(
+        "✓ Imported {} blueprints ({} materials, {} skills)",
+        blueprint_rows.len(),
+        material_count,
+        skill_count
+    );
+    if skipped > 0 {
+        println!("⚠️  Skipped {} blueprints referencing unknown types", skipped);
+    }
+
+    Ok(blueprint_rows.len() as u64 + material_count + skill_count + diff.removed.len() as u64)
+}
+
+pub async fn import_blueprint_activities(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing blueprint invention, copying and research activities...");
+
+    let Some(blueprints) = load_fsd::<HashMap<i32, BlueprintEntry>>(ctx.sde, "blueprints.yaml")? else {
+        return Ok(0);
+    };
+
+    // Blueprints, materials, products and skills must exist in eve_types (foreign keys)
+    let known_types = load_known_type_ids(conn).await?;
+
+    let mut incoming: HashMap<i32, BTreeMap<String, ActivityRecord>> = HashMap::new();
+    let mut skipped = 0;
+
+    for (blueprint_type_id, blueprint) in blueprints {
+        let activities: BTreeMap<String, ActivityRecord> = blueprint.activities
+            .other_activities()
+            .map(|(activity, data)| (activity.to_string(), ActivityRecord::from_sde(data, &known_types)))
+            .collect();
+
+        if activities.is_empty() {
+            continue;
+        }
+        if !known_types.contains(&blueprint_type_id) {
+            skipped += 1;
+            continue;
+        }
+
+        incoming.insert(blueprint_type_id, activities);
+    }
+
+    // Compare against what earlier imports stored (these tables only ever hold SDE data)
+    let mut current: HashMap<i32, BTreeMap<String, ActivityRecord>> = HashMap::new();
+
+    let stored: Vec<(i32, String, Option<i32>)> =
+        sqlx::query_as("SELECT blueprint_type_id, activity, time FROM blueprint_activities")
+            .fetch_all(&mut *conn)
+            .await?;
+    for (blueprint_type_id, activity, time) in stored {
+        current.entry(blueprint_type_id).or_default().insert(activity, ActivityRecord {
+            time,
+            ..Default::default()
+        });
+    }
+
+    let stored: Vec<(i32, String, i32, i32)> = sqlx::query_as(
+        "SELECT blueprint_type_id, activity, material_type_id, quantity FROM blueprint_activity_materials
+         ORDER BY blueprint_type_id, activity, material_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (blueprint_type_id, activity, material_type_id, quantity) in stored {
+        if let Some(record) = current.get_mut(&blueprint_type_id).and_then(|a| a.get_mut(&activity)) {
+            record.materials.push((material_type_id, quantity));
+        }
+    }
+
+    let stored: Vec<(i32, String, i32, i32, Option<f64>)> = sqlx::query_as(
+        "SELECT blueprint_type_id, activity, product_type_id, quantity, probability FROM blueprint_activity_products
+         ORDER BY blueprint_type_id, activity, product_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (blueprint_type_id, activity, product_type_id, quantity, probability) in stored {
+        if let Some(record) = current.get_mut(&blueprint_type_id).and_then(|a| a.get_mut(&activity)) {
+            record.products.push((product_type_id, quantity, probability));
+        }
+    }
+
+    let stored: Vec<(i32, String, i32, i32)> = sqlx::query_as(
+        "SELECT blueprint_type_id, activity, skill_type_id, level FROM blueprint_activity_skills
+         ORDER BY blueprint_type_id, activity, skill_type_id"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (blueprint_type_id, activity, skill_type_id, level) in stored {
+        if let Some(record) = current.get_mut(&blueprint_type_id).and_then(|a| a.get_mut(&activity)) {
+            record.skills.push((skill_type_id, level));
+        }
+    }
+
+    let diff = Diff::compute(&current, &incoming);
+    println!("Blueprint activity changes: {}", diff);
+
+    // Replace every activity of added/changed blueprints and drop those of removed ones
+    let upserts = diff.upserts();
+    for ids in [&upserts, &diff.removed] {
+        for table in [
+            "blueprint_activity_materials",
+            "blueprint_activity_products",
+            "blueprint_activity_skills",
+            "blueprint_activities",
+        ] {
+            let sql = format!("DELETE FROM {} WHERE blueprint_type_id IN ({{ids}})", table);
+            execute_for_ids(conn, &sql, ids).await?;
+        }
+    }
+
+    let activities: Vec<(i32, &str, &ActivityRecord)> = upserts
+        .iter()
+        .flat_map(|blueprint_type_id| {
+            incoming[blueprint_type_id]
+                .iter()
+                .map(|(activity, record)| (*blueprint_type_id, activity.as_str(), record))
+        })
+        .collect();
+
+    let mut progress = Progress::new("activities", activities.len());
+    let mut imported = insert_batched(
+        conn,
+        "INSERT INTO blueprint_activities (blueprint_type_id, activity, time)",
+        "",
+        &activities,
+        &mut progress,
+        |b, (blueprint_type_id, activity, record)| {
+            b.push_bind(*blueprint_type_id).push_bind(*activity).push_bind(record.time);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    let materials: Vec<(i32, &str, i32, i32)> = activities
+        .iter()
+        .flat_map(|(blueprint_type_id, activity, record)| {
+            record.materials
+                .iter()
+                .map(|(material_type_id, quantity)| (*blueprint_type_id, *activity, *material_type_id, *quantity))
+        })
+        .collect();
+
+    imported += insert_batched(
+        conn,
+        "INSERT INTO blueprint_activity_materials (blueprint_type_id, activity, material_type_id, quantity)",
+        "",
+        &materials,
+        &mut Progress::silent(),
+        |b, (blueprint_type_id, activity, material_type_id, quantity)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(*activity)
+                .push_bind(*material_type_id)
+                .push_bind(*quantity);
+        },
+    )
+    .await?;
+
+    let products: Vec<(i32, &str, i32, i32, Option<f64>)> = activities
+        .iter()
+        .flat_map(|(blueprint_type_id, activity, record)| {
+            record.products.iter().map(|(product_type_id, quantity, probability)| {
+                (*blueprint_type_id, *activity, *product_type_id, *quantity, *probability)
+            })
+        })
+        .collect();
+
+    imported += insert_batched(
+        conn,
+        "INSERT INTO blueprint_activity_products
+         (blueprint_type_id, activity, product_type_id, quantity, probability)",
+        "",
+        &products,
+        &mut Progress::silent(),
+        |b, (blueprint_type_id, activity, product_type_id, quantity, probability)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(*activity)
+                .push_bind(*product_type_id)
+                .push_bind(*quantity)
+                .push_bind(*probability);
+        },
+    )
+    .await?;
+
+    let skills: Vec<(i32, &str, i32, i32)> = activities
+        .iter()
+        .flat_map(|(blueprint_type_id, activity, record)| {
+            record.skills
+                .iter()
+                .map(|(skill_type_id, level)| (*blueprint_type_id, *activity, *skill_type_id, *level))
+        })
+        .collect();
+
+    imported += insert_batched(
+        conn,
+        "INSERT INTO blueprint_activity_skills (blueprint_type_id, activity, skill_type_id, level)",
+        "",
+        &skills,
+        &mut Progress::silent(),
+        |b, (blueprint_type_id, activity, skill_type_id, level)| {
+            b.push_bind(*blueprint_type_id)
+                .push_bind(*activity)
+                .push_bind(*skill_type_id)
+                .push_bind(*level);
+        },
+    )
+    .await?;
+
+    diff.record(conn, ctx.run_id, "blueprint_activities").await?;
+
+    println!(
+        "✓ Imported {} activities for {} blueprints ({} materials, {} products, {} skills)",
+        activities.len(),
+        upserts.len(),
+        materials.len(),
+        products.len(),
+        skills.len()
+    );
+    if skipped > 0 {
+        println!("⚠️  Skipped {} blueprints not present in eve_types", skipped);
+    }
+    println!();
+
+    Ok(imported + diff.removed.len() as u64)
+}
@@ -0,0 +1,433 @@
+//! SDE Import Tool
+//!
+//! This tool imports data from EVE Online's Static Data Export (SDE) into the Salvo database.
+//!
+//! Usage:
+//!   cargo run --bin import_sde -- --sde-path /path/to/sde [--resume] [--force]
+//!   cargo run --bin import_sde -- --sde-zip /path/to/sde.zip [--resume] [--force]
+//!   cargo run --bin import_sde -- --validate [--report report.json]
+//!
+//! With `--sde-zip` the YAML files are streamed straight out of the downloaded archive,
+//! so the multi-gigabyte SDE never has to be extracted.
+//! `typeIDs.yaml`, the largest file, is parsed one entry at a time and written in batches
+//! as it is read, so importer memory does not grow with the size of the SDE.
+//!
+//! Each import stage runs in its own transaction and is recorded in `sde_import_stages`
+//! when it commits. If an import is interrupted, `--resume` continues the last unfinished
+//! run from the first stage that did not complete.
+//!
+//! Every run records a checksum of the SDE files. Re-importing compares types, type attributes,
+//! blueprints, blueprint activities and reprocessing yields with what earlier imports stored, applies
+//! only the differences (including removals) and logs them to `sde_import_changes`. An SDE
+//! whose checksum matches the last completed import is skipped unless `--force` is given.
+//!
+//! `--validate` checks the imported data for broken references and ambiguous names, on its
+//! own or after an import. `--report` also writes the results as JSON. The tool exits with
+//! a non-zero status if any check reports errors.
+//!
+//! The SDE can be downloaded from: https://developers.eveonline.com/resources/downloads
+
+mod db;
+mod diff;
+mod dogma;
+mod industry;
+mod npcs;
+mod progress;
+mod sde;
+mod types;
+mod universe;
+mod validate;
+
+use anyhow::Result;
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use progress::{ImportRun, Stage};
+use sde::SdeSource;
+
+/// Where the SDE should be read from
+enum SdeLocation {
+    Directory(PathBuf),
+    Zip(PathBuf),
+}
+
+/// Parsed command line arguments
+struct Args {
+    location: Option<SdeLocation>,
+    resume: bool,
+    force: bool,
+    validate: bool,
+    report: Option<PathBuf>,
+}
+
+/// State shared by every import stage
+pub struct ImportContext<'a> {
+    pub sde: &'a SdeSource,
+    pub run_id: i64,
+}
+
+impl Args {
+    /// Parse arguments, returning None if usage should be printed instead
+    fn parse(args: impl Iterator<Item = String>) -> Result<Option<Self>> {
+        let mut location = None;
+        let mut resume = false;
+        let mut force = false;
+        let mut validate = false;
+        let mut report = None;
+
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--sde-path" | "--sde-zip" => {
+                    let Some(path) = args.next() else {
+                        anyhow::bail!("{} requires a value", arg);
+                    };
+                    if location.is_some() {
+                        anyhow::bail!("Pass only one of --sde-path or --sde-zip");
+                    }
+                    let path = PathBuf::from(path);
+                    location = Some(if arg == "--sde-zip" {
+                        SdeLocation::Zip(path)
+                    } else {
+                        SdeLocation::Directory(path)
+                    });
+                }
+                "--resume" => resume = true,
+                "--force" => force = true,
+                "--validate" => validate = true,
+                "--report" => {
+                    let Some(path) = args.next() else {
+                        anyhow::bail!("--report requires a value");
+                    };
+                    report = Some(PathBuf::from(path));
+                }
+                "--help" | "-h" => return Ok(None),
+                other => anyhow::bail!("Unknown argument: {}", other),
+            }
+        }
+
+        if report.is_some() && !validate {
+            anyhow::bail!("--report requires --validate");
+        }
+        if location.is_none() && !validate {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { location, resume, force, validate, report }))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    println!("=== Salvo SDE Import Tool ===\n");
+
+    // Parse command line arguments
+    let Some(args) = Args::parse(std::env::args())? else {
+        println!("Usage: cargo run --bin import_sde -- --sde-path /path/to/sde [--resume] [--force] [--validate]");
+        println!("       cargo run --bin import_sde -- --sde-zip /path/to/sde.zip [--resume] [--force] [--validate]");
+        println!("       cargo run --bin import_sde -- --validate [--report report.json]");
+        println!("\n  --resume    Continue the last interrupted import, skipping completed stages");
+        println!("  --force     Re-import even if this SDE matches the last completed import");
+        println!("  --validate  Check the imported data for broken references and ambiguous names");
+        println!("  --report    Also write the validation results to this file as JSON");
+        println!("\nThe SDE can be downloaded from:");
+        println!("https://developers.eveonline.com/resources/downloads\n");
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    let sde = match &args.location {
+        Some(SdeLocation::Directory(path)) => Some(SdeSource::directory(path)?),
+        Some(SdeLocation::Zip(path)) => Some(SdeSource::zip(path)?),
+        None => None,
+    };
+
+    // Load environment and connect to database
+    dotenvy::dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "sqlite:salvo.db".to_string());
+
+    println!("Connecting to database: {}", database_url);
+    let pool = SqlitePool::connect(&database_url).await?;
+
+    // Run migrations
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    println!("Database migrations complete\n");
+
+    if let Some(sde) = &sde {
+        import(&pool, sde, &args).await?;
+    }
+
+    if args.validate {
+        println!("\n=== Validating Imported Data ===\n");
+        let report = validate::validate(&pool).await?;
+        report.print();
+
+        if let Some(path) = &args.report {
+            report.write_json(path)?;
+            println!("Report written to {}", path.display());
+        }
+
+        if report.has_errors() {
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Import the SDE, skipping it if it matches the last completed import
+async fn import(pool: &SqlitePool, sde: &SdeSource, args: &Args) -> Result<()> {
+    let checksum = sde::checksum(sde)?;
+    println!("SDE checksum: {}", checksum);
+
+    if !args.resume && !args.force
+        && ImportRun::last_completed_checksum(pool).await?.as_deref() == Some(checksum.as_str())
+    {
+        println!("\nThis SDE was already imported; nothing to do (use --force to re-import)");
+        return Ok(());
+    }
+    println!();
+
+    let mut run = ImportRun::start(pool, &sde.describe(), &checksum, args.resume).await?;
+    let ctx = ImportContext {
+        sde,
+        run_id: run.run_id,
+    };
+
+    for stage in Stage::ALL {
+        if run.is_completed(stage) {
+            println!("↷ Skipping {} (completed earlier in run {})\n", stage.name(), run.run_id);
+            continue;
+        }
+
+        // A stage either commits completely or leaves the database untouched
+        let mut tx = pool.begin().await?;
+        let rows = run_stage(&mut tx, stage, &ctx).await?;
+        run.complete_stage(&mut tx, stage, rows).await?;
+        tx.commit().await?;
+    }
+
+    run.print_changes(pool).await?;
+    run.finish(pool).await?;
+
+    println!("\n=== Import Complete ===");
+    Ok(())
+}
+
+/// Run one import stage, returning the number of rows written
+async fn run_stage(conn: &mut SqliteConnection, stage: Stage, ctx: &ImportContext<'_>) -> Result<u64> {
+    match stage {
+        Stage::Categories => types::import_categories(conn, ctx).await,
+        Stage::Groups => types::import_groups(conn, ctx).await,
+        Stage::MarketGroups => types::import_market_groups(conn, ctx).await,
+        Stage::Types => types::import_type_ids(conn, ctx).await,
+        Stage::TypeMaterials => industry::import_type_materials(conn, ctx).await,
+        Stage::ReprocessingSkills => industry::import_reprocessing_skills(conn, ctx).await,
+        Stage::TypeAttributes => dogma::import_type_attributes(conn, ctx).await,
+        Stage::Blueprints => industry::import_blueprints(conn, ctx).await,
+        Stage::BlueprintActivities => industry::import_blueprint_activities(conn, ctx).await,
+        Stage::Universe => universe::import_universe(conn, ctx).await,
+        Stage::NpcTypes => npcs::import_npc_types(conn, ctx).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use salvo_backend::test_support;
+    use std::path::Path;
+
+    /// Small SDE covering every import stage: one manufacturing blueprint with its
+    /// invention/research activities, a reprocessable salvage item and Jita's station
+    fn fixture_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/bin/import_sde/testdata/sde")
+    }
+
+    /// Writable copy of the fixture SDE, for tests that edit it between imports
+    fn fixture_copy(name: &str) -> PathBuf {
+        fn copy_dir(from: &Path, to: &Path) {
+            std::fs::create_dir_all(to).unwrap();
+            for entry in std::fs::read_dir(from).unwrap() {
+                let entry = entry.unwrap();
+                let target = to.join(entry.file_name());
+                if entry.file_type().unwrap().is_dir() {
+                    copy_dir(&entry.path(), &target);
+                } else {
+                    std::fs::copy(entry.path(), target).unwrap();
+                }
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("salvo-sde-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        copy_dir(&fixture_path(), &dir);
+        dir
+    }
+
+    fn import_args(resume: bool, force: bool) -> Args {
+        Args { location: None, resume, force, validate: false, report: None }
+    }
+
+    async fn completed_stages(pool: &SqlitePool, run_id: i64) -> Vec<String> {
+        sqlx::query_scalar("SELECT stage FROM sde_import_stages WHERE run_id = ? ORDER BY stage")
+            .bind(run_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn run_ids(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT run_id FROM sde_import_runs ORDER BY run_id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn category_name(pool: &SqlitePool) -> String {
+        sqlx::query_scalar("SELECT name FROM eve_categories WHERE category_id = 16")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_fixture_blueprints() {
+        let pool = test_support::pool().await;
+        let sde = SdeSource::directory(&fixture_path()).unwrap();
+
+        import(&pool, &sde, &import_args(false, false)).await.unwrap();
+
+        let blueprint: (i32, Option<i32>, Option<i32>) = sqlx::query_as(
+            "SELECT product_type_id, manufacturing_time, max_production_limit
+             FROM blueprints WHERE blueprint_type_id = 26906"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(blueprint, (26898, Some(600), Some(300)));
+
+        let materials: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT material_type_id, quantity FROM blueprint_materials
+             WHERE blueprint_type_id = 26906 ORDER BY material_type_id"
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(materials, vec![(34, 86), (35, 12)]);
+
+        let skills: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT skill_type_id, level FROM blueprint_skills WHERE blueprint_type_id = 26906"
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(skills, vec![(3380, 3)]);
+
+        let run_id = run_ids(&pool).await[0];
+        assert_eq!(completed_stages(&pool, run_id).await.len(), Stage::ALL.len());
+
+        // The same SDE again is a no-op without --force
+        import(&pool, &sde, &import_args(false, false)).await.unwrap();
+        assert_eq!(run_ids(&pool).await, vec![run_id]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_completed_stages_and_force_reruns_them() {
+        let pool = test_support::pool().await;
+        let sde = SdeSource::directory(&fixture_path()).unwrap();
+
+        // Fail the blueprints stage; every stage before it has already committed
+        sqlx::query(
+            "CREATE TRIGGER fail_blueprints BEFORE INSERT ON blueprints
+             BEGIN SELECT RAISE(ABORT, 'injected failure'); END"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert!(import(&pool, &sde, &import_args(false, false)).await.is_err());
+
+        let run_id = run_ids(&pool).await[0];
+        let done = completed_stages(&pool, run_id).await;
+        assert_eq!(done.len(), 7);
+        assert!(done.contains(&"types".to_string()));
+        assert!(!done.contains(&"blueprints".to_string()));
+
+        // A skipped stage would not restore this name
+        sqlx::query("DROP TRIGGER fail_blueprints").execute(&pool).await.unwrap();
+        sqlx::query("UPDATE eve_categories SET name = 'Edited' WHERE category_id = 16")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        import(&pool, &sde, &import_args(true, false)).await.unwrap();
+
+        assert_eq!(run_ids(&pool).await, vec![run_id]);
+        assert_eq!(completed_stages(&pool, run_id).await.len(), Stage::ALL.len());
+        assert_eq!(category_name(&pool).await, "Edited");
+
+        // --force re-imports the same SDE in a new run that runs every stage
+        import(&pool, &sde, &import_args(false, true)).await.unwrap();
+
+        let runs = run_ids(&pool).await;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(completed_stages(&pool, runs[1]).await.len(), Stage::ALL.len());
+        assert_eq!(category_name(&pool).await, "Skill");
+    }
+
+    #[tokio::test]
+    async fn test_reimport_removes_dependents_and_logs_changes() {
+        let pool = test_support::pool().await;
+        let sde_path = fixture_copy("reimport");
+        let sde = SdeSource::directory(&sde_path).unwrap();
+
+        import(&pool, &sde, &import_args(false, false)).await.unwrap();
+
+        // Drop Pyerite, rename Tritanium and add Mexallon
+        let type_ids_path = sde_path.join("fsd/typeIDs.yaml");
+        let type_ids = std::fs::read_to_string(&type_ids_path).unwrap();
+        let pyerite = type_ids.find("35:\n").unwrap();
+        let after_pyerite = pyerite + type_ids[pyerite..].find("26906:\n").unwrap();
+        let type_ids = format!(
+            "{}{}36:\n    groupID: 18\n    name:\n        en: Mexallon\n    portionSize: 1\n    published: true\n",
+            &type_ids[..pyerite],
+            &type_ids[after_pyerite..],
+        )
+        .replace("en: Tritanium", "en: Tritanium II");
+        std::fs::write(&type_ids_path, type_ids).unwrap();
+
+        let sde = SdeSource::directory(&sde_path).unwrap();
+        import(&pool, &sde, &import_args(false, false)).await.unwrap();
+        std::fs::remove_dir_all(&sde_path).unwrap();
+
+        let count = |sql: &'static str| {
+            let pool = pool.clone();
+            async move { sqlx::query_scalar::<_, i64>(sql).fetch_one(&pool).await.unwrap() }
+        };
+        assert_eq!(count("SELECT COUNT(*) FROM eve_types WHERE type_id = 35").await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM blueprint_materials WHERE blueprint_type_id = 26906 AND material_type_id = 35").await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM material_reprocessing WHERE material_type_id = 35").await, 0);
+        assert_eq!(count("SELECT COUNT(*) FROM blueprint_materials WHERE blueprint_type_id = 26906 AND material_type_id = 34").await, 1);
+
+        let run_id = *run_ids(&pool).await.last().unwrap();
+        let changes: Vec<(i32, String)> = sqlx::query_as(
+            "SELECT entity_id, change FROM sde_import_changes
+             WHERE run_id = ? AND entity = 'type' ORDER BY entity_id"
+        )
+        .bind(run_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                (34, "changed".to_string()),
+                (35, "removed".to_string()),
+                (36, "added".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use sqlx::sqlite::SqliteConnection;
+
+use crate::db::insert_batched;
+use crate::progress::Progress;
+use crate::ImportContext;
+
+/// SDE category for NPC entities
+const ENTITY_CATEGORY_ID: i32 = 11;
+
+/// Pirate factions as they appear in NPC group names, with the key stored in npc_types
+const FACTIONS: [(&str, &str); 5] = [
+    ("Serpentis", "serpentis"),
+    ("Guristas", "guristas"),
+    ("Angel Cartel", "angel_cartel"),
+    ("Blood Raider", "blood_raiders"),
+    ("Sansha", "sanshas_nation"),
+];
+
+/// Hull classes in NPC group names and the wreck each leaves; longer names come first
+/// so "Battlecruiser" is not read as "Cruiser"
+const SHIP_CLASSES: [(&str, &str, &str); 7] = [
+    ("battlecruiser", "battlecruiser", "medium"),
+    ("battleship", "battleship", "large"),
+    ("destroyer", "destroyer", "small"),
+    ("frigate", "frigate", "small"),
+    ("cruiser", "cruiser", "medium"),
+    ("hauler", "hauler", "medium"),
+    ("officer", "officer", "large"),
+];
+
+/// An NPC ship classified from its group name
+struct NpcRecord {
+    type_id: i32,
+    faction: &'static str,
+    ship_class: &'static str,
+    wreck_size: &'static str,
+}
+
+/// Classify an NPC group name (e.g. "Asteroid Serpentis Battleship") into
+/// (faction, ship class, wreck size); None for groups that are not pirate ships
+fn classify_group(group_name: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    let faction = FACTIONS
+        .iter()
+        .find(|(pattern, _)| group_name.contains(pattern))
+        .map(|(_, faction)| *faction)?;
+
+    let lower = group_name.to_lowercase();
+    let (_, ship_class, wreck_size) = SHIP_CLASSES
+        .iter()
+        .find(|(pattern, _, _)| lower.contains(pattern))?;
+
+    Some((faction, ship_class, wreck_size))
+}
+
+/// Derive pirate NPC ships from the NPC types and groups imported by earlier stages
+///
+/// The SDE has no loot tables; what each wreck yields lives in `wreck_salvage`,
+/// keyed by the faction and wreck size recorded here.
+pub async fn import_npc_types(conn: &mut SqliteConnection, _ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing NPC ship types...");
+
+    let candidates: Vec<(i32, String)> = sqlx::query_as(
+        "SELECT t.type_id, g.name
+         FROM eve_types t
+         JOIN eve_groups g ON t.group_id = g.group_id
+         WHERE g.category_id = ?
+         ORDER BY t.type_id"
+    )
+    .bind(ENTITY_CATEGORY_ID)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let npcs: Vec<NpcRecord> = candidates
+        .iter()
+        .filter_map(|(type_id, group_name)| {
+            let (faction, ship_class, wreck_size) = classify_group(group_name)?;
+            Some(NpcRecord { type_id: *type_id, faction, ship_class, wreck_size })
+        })
+        .collect();
+
+    // Derived entirely from eve_types/eve_groups, so rebuilt every import
+    sqlx::query("DELETE FROM npc_types").execute(&mut *conn).await?;
+
+    let mut progress = Progress::new("NPC ships", npcs.len());
+    let imported = insert_batched(
+        conn,
+        "INSERT INTO npc_types (type_id, faction, ship_class, wreck_size)",
+        "",
+        &npcs,
+        &mut progress,
+        |b, npc| {
+            b.push_bind(npc.type_id)
+                .push_bind(npc.faction)
+                .push_bind(npc.ship_class)
+                .push_bind(npc.wreck_size);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    println!("✓ Imported {} pirate NPC ships ({} NPC types checked)\n", npcs.len(), candidates.len());
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_group() {
+        assert_eq!(
+            classify_group("Asteroid Serpentis Frigate"),
+            Some(("serpentis", "frigate", "small"))
+        );
+        assert_eq!(
+            classify_group("Asteroid Serpentis BattleCruiser"),
+            Some(("serpentis", "battlecruiser", "medium"))
+        );
+        assert_eq!(
+            classify_group("Deadspace Serpentis Battleship"),
+            Some(("serpentis", "battleship", "large"))
+        );
+        assert_eq!(
+            classify_group("Asteroid Blood Raiders Cruiser"),
+            Some(("blood_raiders", "cruiser", "medium"))
+        );
+        assert_eq!(classify_group("Asteroid Serpentis Commander"), None);
+        assert_eq!(classify_group("Mission Gallente Federation Frigate"), None);
+    }
+}
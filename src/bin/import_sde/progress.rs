@@ -0,0 +1,243 @@
+use anyhow::Result;
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use std::io::Write;
+
+/// Import stages, in the order they must run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    Categories,
+    Groups,
+    MarketGroups,
+    Types,
+    TypeMaterials,
+    ReprocessingSkills,
+    TypeAttributes,
+    Blueprints,
+    BlueprintActivities,
+    Universe,
+    NpcTypes,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 11] = [
+        Stage::Categories,
+        Stage::Groups,
+        Stage::MarketGroups,
+        Stage::Types,
+        Stage::TypeMaterials,
+        Stage::ReprocessingSkills,
+        Stage::TypeAttributes,
+        Stage::Blueprints,
+        Stage::BlueprintActivities,
+        Stage::Universe,
+        Stage::NpcTypes,
+    ];
+
+    /// Name stored in sde_import_stages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Categories => "categories",
+            Stage::Groups => "groups",
+            Stage::MarketGroups => "market_groups",
+            Stage::Types => "types",
+            Stage::TypeMaterials => "type_materials",
+            Stage::ReprocessingSkills => "reprocessing_skills",
+            Stage::TypeAttributes => "type_attributes",
+            Stage::Blueprints => "blueprints",
+            Stage::BlueprintActivities => "blueprint_activities",
+            Stage::Universe => "universe",
+            Stage::NpcTypes => "npc_types",
+        }
+    }
+}
+
+/// A recorded import run and the stages it has completed
+pub struct ImportRun {
+    pub run_id: i64,
+    completed: HashSet<&'static str>,
+}
+
+impl ImportRun {
+    /// Checksum of the most recent fully completed import, if any
+    pub async fn last_completed_checksum(pool: &SqlitePool) -> Result<Option<String>> {
+        let checksum: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT sde_checksum FROM sde_import_runs
+             WHERE completed_at IS NOT NULL
+             ORDER BY run_id DESC LIMIT 1"
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(checksum.flatten())
+    }
+
+    /// Start a new run, or pick up the last unfinished run for the same SDE when resuming
+    pub async fn start(
+        pool: &SqlitePool,
+        sde_source: &str,
+        checksum: &str,
+        resume: bool,
+    ) -> Result<Self> {
+        if resume {
+            let unfinished: Option<(i64, String, Option<String>)> = sqlx::query_as(
+                "SELECT run_id, sde_source, sde_checksum FROM sde_import_runs
+                 WHERE completed_at IS NULL
+                 ORDER BY run_id DESC LIMIT 1"
+            )
+            .fetch_optional(pool)
+            .await?;
+
+            match unfinished {
+                Some((run_id, source, run_checksum))
+                    if source == sde_source && run_checksum.as_deref() == Some(checksum) =>
+                {
+                    let stages: Vec<String> = sqlx::query_scalar(
+                        "SELECT stage FROM sde_import_stages WHERE run_id = ?"
+                    )
+                    .bind(run_id)
+                    .fetch_all(pool)
+                    .await?;
+
+                    let completed = Stage::ALL
+                        .iter()
+                        .map(Stage::name)
+                        .filter(|name| stages.iter().any(|s| s == name))
+                        .collect();
+
+                    println!("Resuming import run {} ({} of {} stages done)\n",
+                        run_id, stages.len(), Stage::ALL.len());
+                    return Ok(Self { run_id, completed });
+                }
+                Some((run_id, source, _)) => {
+                    anyhow::bail!(
+                        "Unfinished run {} was importing a different SDE ({}); run without --resume to start over",
+                        run_id, source
+                    );
+                }
+                None => println!("No unfinished import to resume, starting a new run\n"),
+            }
+        }
+
+        let run_id = sqlx::query("INSERT INTO sde_import_runs (sde_source, sde_checksum) VALUES (?, ?)")
+            .bind(sde_source)
+            .bind(checksum)
+            .execute(pool)
+            .await?
+            .last_insert_rowid();
+
+        Ok(Self {
+            run_id,
+            completed: HashSet::new(),
+        })
+    }
+
+    pub fn is_completed(&self, stage: Stage) -> bool {
+        self.completed.contains(stage.name())
+    }
+
+    /// Record a stage as complete (inside the stage's transaction)
+    pub async fn complete_stage(
+        &mut self,
+        conn: &mut SqliteConnection,
+        stage: Stage,
+        rows: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sde_import_stages (run_id, stage, rows_imported) VALUES (?, ?, ?)"
+        )
+        .bind(self.run_id)
+        .bind(stage.name())
+        .bind(rows as i64)
+        .execute(conn)
+        .await?;
+
+        self.completed.insert(stage.name());
+        Ok(())
+    }
+
+    /// Print the number of added/changed/removed records per entity for this run
+    pub async fn print_changes(&self, pool: &SqlitePool) -> Result<()> {
+        let counts: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT entity, change, COUNT(*) FROM sde_import_changes
+             WHERE run_id = ?
+             GROUP BY entity, change
+             ORDER BY entity, change"
+        )
+        .bind(self.run_id)
+        .fetch_all(pool)
+        .await?;
+
+        println!("\nChanges applied in run {}:", self.run_id);
+        if counts.is_empty() {
+            println!("  (none)");
+        }
+        for (entity, change, count) in counts {
+            println!("  {:<20} {:<8} {}", entity, change, count);
+        }
+
+        Ok(())
+    }
+
+    /// Mark the whole run as finished
+    pub async fn finish(&self, pool: &SqlitePool) -> Result<()> {
+        sqlx::query("UPDATE sde_import_runs SET completed_at = CURRENT_TIMESTAMP WHERE run_id = ?")
+            .bind(self.run_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Console progress indicator for a stage
+pub struct Progress {
+    label: &'static str,
+    /// None when streaming input of unknown length
+    total: Option<usize>,
+    done: usize,
+    /// Counts without printing, for secondary inserts within a stage
+    silent: bool,
+}
+
+impl Progress {
+    pub fn new(label: &'static str, total: usize) -> Self {
+        Self { label, total: Some(total), done: 0, silent: false }
+    }
+
+    /// Progress for a stream whose length is not known up front
+    pub fn unbounded(label: &'static str) -> Self {
+        Self { label, total: None, done: 0, silent: false }
+    }
+
+    /// Progress that is tracked but never printed
+    pub fn silent() -> Self {
+        Self { label: "", total: None, done: 0, silent: true }
+    }
+
+    pub fn advance(&mut self, count: usize) {
+        self.done += count;
+        if self.silent {
+            return;
+        }
+
+        match self.total {
+            Some(total) => {
+                let percent = if total == 0 {
+                    100.0
+                } else {
+                    self.done as f64 / total as f64 * 100.0
+                };
+                print!("\r  {}: {}/{} ({:.0}%)", self.label, self.done, total, percent);
+            }
+            None => print!("\r  {}: {}", self.label, self.done),
+        }
+        std::io::stdout().flush().ok();
+    }
+
+    pub fn finish(&self) {
+        if self.done > 0 && !self.silent {
+            println!();
+        }
+    }
+}
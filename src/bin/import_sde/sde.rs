@@ -0,0 +1,399 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use zip::ZipArchive;
+
+/// Entries buffered between the YAML reader thread and the importer
+const STREAM_BUFFER: usize = 1000;
+
+/// SDE files read by the importer (relative to the SDE root); together with everything
+/// under [`UNIVERSE_DIR`] these make up the import checksum
+pub const SDE_FILES: &[&str] = &[
+    "fsd/categoryIDs.yaml",
+    "fsd/groupIDs.yaml",
+    "fsd/marketGroups.yaml",
+    "fsd/typeIDs.yaml",
+    "fsd/typeMaterials.yaml",
+    "fsd/blueprints.yaml",
+    "fsd/typeDogma.yaml",
+    "bsd/invNames.yaml",
+    "bsd/staStations.yaml",
+];
+
+/// Region/constellation/solar system tree, one `.staticdata` file per celestial
+pub const UNIVERSE_DIR: &str = "fsd/universe/";
+
+/// Where SDE files are read from
+#[derive(Clone)]
+pub enum SdeSource {
+    /// Extracted SDE directory containing `fsd/` and `bsd/`
+    Directory(PathBuf),
+    /// SDE zip archive; entries are decompressed on the fly, never written to disk
+    Zip {
+        path: PathBuf,
+        /// Path relative to the SDE root (e.g. `fsd/typeIDs.yaml`) -> entry name inside the archive
+        entries: HashMap<String, String>,
+    },
+}
+
+impl SdeSource {
+    pub fn directory(path: &Path) -> Result<Self> {
+        if !path.is_dir() {
+            anyhow::bail!("SDE path does not exist: {}", path.display());
+        }
+
+        Ok(SdeSource::Directory(path.to_path_buf()))
+    }
+
+    /// Open an SDE zip and index its `fsd/` and `bsd/` entries
+    ///
+    /// Entries are matched from their `fsd/` or `bsd/` component onwards, so archives with
+    /// a top-level `sde/` folder work too.
+    pub fn zip(path: &Path) -> Result<Self> {
+        let archive = open_archive(path)?;
+
+        let entries = archive
+            .file_names()
+            .filter(|entry_name| !entry_name.ends_with('/'))
+            .filter_map(|entry_name| {
+                let start = ["fsd/", "bsd/"].iter().find_map(|root| {
+                    if entry_name.starts_with(root) {
+                        Some(0)
+                    } else {
+                        entry_name.find(&format!("/{}", root)).map(|i| i + 1)
+                    }
+                })?;
+                Some((entry_name[start..].to_string(), entry_name.to_string()))
+            })
+            .collect();
+
+        Ok(SdeSource::Zip {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Human-readable location, recorded on each import run
+    pub fn describe(&self) -> String {
+        match self {
+            SdeSource::Directory(path) => path.display().to_string(),
+            SdeSource::Zip { path, .. } => format!("zip:{}", path.display()),
+        }
+    }
+
+    /// Whether the file at `relative_path` (e.g. `fsd/typeIDs.yaml`) is present
+    pub fn has_file(&self, relative_path: &str) -> bool {
+        match self {
+            SdeSource::Directory(sde_path) => sde_path.join(relative_path).is_file(),
+            SdeSource::Zip { entries, .. } => entries.contains_key(relative_path),
+        }
+    }
+
+    /// Every file below `prefix` (relative to the SDE root), sorted by path
+    pub fn list_files(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut files = match self {
+            SdeSource::Directory(sde_path) => {
+                let mut files = Vec::new();
+                let dir = sde_path.join(prefix);
+                if dir.is_dir() {
+                    walk_dir(&dir, prefix.trim_end_matches('/'), &mut files)?;
+                }
+                files
+            }
+            SdeSource::Zip { entries, .. } => entries
+                .keys()
+                .filter(|relative_path| relative_path.starts_with(prefix))
+                .cloned()
+                .collect(),
+        };
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// Open `relative_path` and hand a reader to `read`, or return None if the file is absent
+    pub fn with_reader<T>(
+        &self,
+        relative_path: &str,
+        read: impl FnOnce(&mut dyn Read) -> Result<T>,
+    ) -> Result<Option<T>> {
+        if !self.has_file(relative_path) {
+            return Ok(None);
+        }
+
+        let mut result = None;
+        let mut read = Some(read);
+        self.for_each_file(&[relative_path.to_string()], |_, reader| {
+            if let Some(read) = read.take() {
+                result = Some(read(reader)?);
+            }
+            Ok(())
+        })?;
+
+        Ok(result)
+    }
+
+    /// Read several files in order, opening the zip archive only once
+    ///
+    /// Files that are not present are skipped.
+    pub fn for_each_file(
+        &self,
+        relative_paths: &[String],
+        mut read: impl FnMut(&str, &mut dyn Read) -> Result<()>,
+    ) -> Result<()> {
+        match self {
+            SdeSource::Directory(sde_path) => {
+                for relative_path in relative_paths {
+                    let path = sde_path.join(relative_path);
+                    if !path.is_file() {
+                        continue;
+                    }
+
+                    let file = File::open(&path)
+                        .with_context(|| format!("Failed to open {}", relative_path))?;
+                    read(relative_path, &mut BufReader::new(file))?;
+                }
+            }
+            SdeSource::Zip { path, entries } => {
+                let mut archive = open_archive(path)?;
+
+                for relative_path in relative_paths {
+                    let Some(entry_name) = entries.get(relative_path) else {
+                        continue;
+                    };
+
+                    let mut entry = archive
+                        .by_name(entry_name)
+                        .with_context(|| format!("Failed to open {} in archive", entry_name))?;
+                    read(relative_path, &mut entry)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<BufReader<File>>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open SDE zip {}", path.display()))?;
+
+    ZipArchive::new(BufReader::new(file))
+        .with_context(|| format!("Failed to read SDE zip {}", path.display()))
+}
+
+/// Collect files below `dir` as `/`-separated paths starting with `relative`
+fn walk_dir(dir: &Path, relative: &str, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let relative_path = format!("{}/{}", relative, entry.file_name().to_string_lossy());
+
+        if entry.file_type()?.is_dir() {
+            walk_dir(&entry.path(), &relative_path, files)?;
+        } else {
+            files.push(relative_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// SHA-256 over every SDE file the importer reads, identifying the SDE build being imported
+///
+/// The checksum covers file contents only, so a zip and its extracted directory match.
+pub fn checksum(sde: &SdeSource) -> Result<String> {
+    let mut files: Vec<String> = SDE_FILES.iter().map(|f| f.to_string()).collect();
+    files.extend(sde.list_files(UNIVERSE_DIR)?);
+
+    let mut hasher = Sha256::new();
+    sde.for_each_file(&files, |relative_path, reader| {
+        hasher.update(relative_path.as_bytes());
+        io::copy(reader, &mut hasher)
+            .with_context(|| format!("Failed to read {}", relative_path))?;
+        Ok(())
+    })?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Read and parse `fsd/<file_name>` from the SDE, or None if the file is not present
+pub fn load_fsd<T: DeserializeOwned>(sde: &SdeSource, file_name: &str) -> Result<Option<T>> {
+    load_file(sde, &format!("fsd/{}", file_name))
+}
+
+/// Read and parse a YAML file (path relative to the SDE root), or None if it is not present
+pub fn load_file<T: DeserializeOwned>(sde: &SdeSource, relative_path: &str) -> Result<Option<T>> {
+    let parsed = sde.with_reader(relative_path, |reader| {
+        serde_yaml::from_reader(reader).with_context(|| format!("Failed to parse {}", relative_path))
+    })?;
+
+    if parsed.is_none() {
+        println!("⚠️  {} not found, skipping", relative_path);
+    }
+
+    Ok(parsed)
+}
+
+/// Stream the entries of a top-level `fsd/<file_name>` mapping one at a time
+///
+/// The file is read and parsed on a blocking thread that sends `(id, entry)` pairs through
+/// a bounded channel, so only a handful of entries are ever held in memory no matter how
+/// large the file is. Returns None if the file is not present.
+pub fn stream_fsd<T>(sde: &SdeSource, file_name: &str) -> Option<mpsc::Receiver<Result<(i32, T)>>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    stream_yaml::<HashMap<i32, T>, _>(sde, format!("fsd/{}", file_name))
+}
+
+/// Stream the items of a top-level YAML list (e.g. `bsd/invNames.yaml`) one at a time
+pub fn stream_list<T>(sde: &SdeSource, relative_path: &str) -> Option<mpsc::Receiver<Result<T>>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    stream_yaml::<Vec<T>, _>(sde, relative_path.to_string())
+}
+
+/// Parse a top-level YAML collection `C` one entry at a time on a blocking thread
+fn stream_yaml<C, T>(sde: &SdeSource, relative_path: String) -> Option<mpsc::Receiver<Result<T>>>
+where
+    C: DeserializeOwned + IntoIterator<Item = T>,
+    T: Send + 'static,
+{
+    if !sde.has_file(&relative_path) {
+        println!("⚠️  {} not found, skipping", relative_path);
+        return None;
+    }
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    let sde = sde.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let result = sde.with_reader(&relative_path, |reader| {
+            let mut entries = MapEntries::new(BufReader::new(reader));
+
+            while let Some(chunk) = entries.next_chunk()? {
+                let entry: C = serde_yaml::from_str(&chunk)
+                    .with_context(|| format!("Failed to parse {} entry", relative_path))?;
+
+                for item in entry {
+                    // Receiver dropped: the importer bailed out, stop reading
+                    if tx.blocking_send(Ok(item)).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            tx.blocking_send(Err(e)).ok();
+        }
+    });
+
+    Some(rx)
+}
+
+/// Splits a top-level YAML mapping or list into one small YAML document per entry
+///
+/// SDE files are mappings keyed by id (or lists of `- ` items) at column 0 with every value
+/// indented below, so each line starting at column 0 begins a new entry.
+struct MapEntries<R> {
+    reader: R,
+    /// Key line of the next entry, read while finishing the previous one
+    next_key_line: Option<String>,
+    first_line: bool,
+}
+
+impl<R: BufRead> MapEntries<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            next_key_line: None,
+            first_line: true,
+        }
+    }
+
+    /// Next entry as standalone YAML text, or None at end of file
+    fn next_chunk(&mut self) -> Result<Option<String>> {
+        let mut chunk = self.next_key_line.take().unwrap_or_default();
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+
+            if self.first_line {
+                self.first_line = false;
+                if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                    line = stripped.to_string();
+                }
+            }
+
+            let starts_entry = line.starts_with(|c: char| !c.is_whitespace() && c != '#')
+                && line.trim_end() != "---";
+
+            if starts_entry && !chunk.is_empty() {
+                self.next_key_line = Some(line);
+                return Ok(Some(chunk));
+            }
+
+            if starts_entry || !chunk.is_empty() {
+                chunk.push_str(&line);
+            }
+        }
+
+        Ok((!chunk.is_empty()).then_some(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_entries_splits_top_level_keys() {
+        let yaml = "# comment\n34:\n    name:\n        en: Tritanium\n    volume: 0.01\n\n35:\n    description:\n        en: 'Line one\n\n            line two'\n    name:\n        en: Pyerite\n";
+        let mut entries = MapEntries::new(yaml.as_bytes());
+
+        let first = entries.next_chunk().unwrap().unwrap();
+        let second = entries.next_chunk().unwrap().unwrap();
+        assert!(entries.next_chunk().unwrap().is_none());
+
+        assert!(first.starts_with("34:"));
+        assert!(second.starts_with("35:"));
+
+        let parsed: HashMap<i32, serde_yaml::Value> = serde_yaml::from_str(&second).unwrap();
+        assert_eq!(parsed[&35]["name"]["en"].as_str(), Some("Pyerite"));
+        assert_eq!(parsed[&35]["description"]["en"].as_str(), Some("Line one\nline two"));
+    }
+
+    #[test]
+    fn test_map_entries_splits_list_items() {
+        let yaml = "-   itemID: 10000002\n    itemName: The Forge\n-   itemID: 30000142\n    itemName: Jita\n";
+        let mut entries = MapEntries::new(yaml.as_bytes());
+
+        let first = entries.next_chunk().unwrap().unwrap();
+        let second = entries.next_chunk().unwrap().unwrap();
+        assert!(entries.next_chunk().unwrap().is_none());
+
+        let parsed: Vec<serde_yaml::Value> = serde_yaml::from_str(&first).unwrap();
+        assert_eq!(parsed[0]["itemName"].as_str(), Some("The Forge"));
+        let parsed: Vec<serde_yaml::Value> = serde_yaml::from_str(&second).unwrap();
+        assert_eq!(parsed[0]["itemID"].as_i64(), Some(30000142));
+    }
+
+    #[test]
+    fn test_map_entries_empty_input() {
+        let mut entries = MapEntries::new("".as_bytes());
+        assert!(entries.next_chunk().unwrap().is_none());
+    }
+}
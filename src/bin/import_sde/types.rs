@@ -0,0 +1,448 @@
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::sqlite::{Sqlite, SqliteConnection};
+use sqlx::QueryBuilder;
+use std::collections::{HashMap, HashSet};
+
+use crate::db::{execute_for_ids, insert_batched, BATCH_SIZE};
+use crate::diff::Diff;
+use crate::progress::Progress;
+use crate::sde::{load_fsd, stream_fsd};
+use crate::ImportContext;
+
+#[derive(Debug, Deserialize)]
+struct TypeId {
+    #[serde(rename = "groupID")]
+    group_id: Option<i32>,
+    #[serde(rename = "name")]
+    name: HashMap<String, String>,
+    description: Option<HashMap<String, String>>,
+    volume: Option<f64>,
+    #[serde(rename = "basePrice")]
+    base_price: Option<f64>,
+    #[serde(rename = "marketGroupID")]
+    market_group_id: Option<i32>,
+    #[serde(rename = "portionSize")]
+    portion_size: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoryId {
+    name: HashMap<String, String>,
+    published: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupId {
+    #[serde(rename = "categoryID")]
+    category_id: i32,
+    name: HashMap<String, String>,
+    published: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarketGroupId {
+    #[serde(rename = "nameID")]
+    name: HashMap<String, String>,
+    #[serde(rename = "descriptionID")]
+    description: Option<HashMap<String, String>>,
+    #[serde(rename = "parentGroupID")]
+    parent_group_id: Option<i32>,
+    #[serde(rename = "hasTypes")]
+    has_types: Option<bool>,
+}
+
+/// English entry of a localized SDE string, with a placeholder for untranslated ids
+fn english_name(names: &HashMap<String, String>, id: i32) -> String {
+    names.get("en")
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("Unknown {}", id))
+}
+
+pub async fn import_categories(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing categories...");
+
+    let Some(categories) = load_fsd::<HashMap<i32, CategoryId>>(ctx.sde, "categoryIDs.yaml")? else {
+        return Ok(0);
+    };
+
+    let rows: Vec<(i32, String, bool)> = categories
+        .into_iter()
+        .map(|(category_id, category)| {
+            let name = english_name(&category.name, category_id);
+            (category_id, name, category.published.unwrap_or(false))
+        })
+        .collect();
+
+    let mut progress = Progress::new("categories", rows.len());
+    insert_batched(
+        conn,
+        "INSERT INTO eve_categories (category_id, name, published)",
+        "ON CONFLICT(category_id) DO UPDATE SET
+            name = excluded.name,
+            published = excluded.published",
+        &rows,
+        &mut progress,
+        |b, (category_id, name, published)| {
+            b.push_bind(*category_id).push_bind(name.as_str()).push_bind(*published);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    println!("✓ Imported {} categories\n", rows.len());
+    Ok(rows.len() as u64)
+}
+
+pub async fn import_groups(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing groups...");
+
+    let Some(groups) = load_fsd::<HashMap<i32, GroupId>>(ctx.sde, "groupIDs.yaml")? else {
+        return Ok(0);
+    };
+
+    let known_categories: HashSet<i32> = sqlx::query_scalar("SELECT category_id FROM eve_categories")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let rows: Vec<(i32, i32, String, bool)> = groups
+        .into_iter()
+        .filter(|(_, group)| known_categories.contains(&group.category_id))
+        .map(|(group_id, group)| {
+            let name = english_name(&group.name, group_id);
+            (group_id, group.category_id, name, group.published.unwrap_or(false))
+        })
+        .collect();
+
+    let mut progress = Progress::new("groups", rows.len());
+    insert_batched(
+        conn,
+        "INSERT INTO eve_groups (group_id, category_id, name, published)",
+        "ON CONFLICT(group_id) DO UPDATE SET
+            category_id = excluded.category_id,
+            name = excluded.name,
+            published = excluded.published",
+        &rows,
+        &mut progress,
+        |b, (group_id, category_id, name, published)| {
+            b.push_bind(*group_id)
+                .push_bind(*category_id)
+                .push_bind(name.as_str())
+                .push_bind(*published);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    println!("✓ Imported {} groups\n", rows.len());
+    Ok(rows.len() as u64)
+}
+
+pub async fn import_market_groups(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing market groups...");
+
+    let Some(market_groups) = load_fsd::<HashMap<i32, MarketGroupId>>(ctx.sde, "marketGroups.yaml")? else {
+        return Ok(0);
+    };
+
+    let rows: Vec<(i32, MarketGroupId, String)> = market_groups
+        .into_iter()
+        .map(|(market_group_id, group)| {
+            let name = english_name(&group.name, market_group_id);
+            (market_group_id, group, name)
+        })
+        .collect();
+
+    let mut progress = Progress::new("market groups", rows.len());
+    insert_batched(
+        conn,
+        "INSERT INTO market_groups (market_group_id, parent_group_id, name, description, has_types)",
+        "ON CONFLICT(market_group_id) DO UPDATE SET
+            parent_group_id = excluded.parent_group_id,
+            name = excluded.name,
+            description = excluded.description,
+            has_types = excluded.has_types",
+        &rows,
+        &mut progress,
+        |b, (market_group_id, group, name)| {
+            let description = group.description
+                .as_ref()
+                .and_then(|d| d.get("en"))
+                .map(|s| s.as_str());
+
+            b.push_bind(*market_group_id)
+                .push_bind(group.parent_group_id)
+                .push_bind(name.as_str())
+                .push_bind(description)
+                .push_bind(group.has_types.unwrap_or(false));
+        },
+    )
+    .await?;
+    progress.finish();
+
+    println!("✓ Imported {} market groups\n", rows.len());
+    Ok(rows.len() as u64)
+}
+
+pub async fn import_type_ids(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing type IDs...");
+
+    // typeIDs.yaml is by far the largest SDE file: stream it entry by entry and write
+    // each batch as it fills instead of parsing the whole file up front
+    let Some(mut entries) = stream_fsd::<TypeId>(ctx.sde, "typeIDs.yaml") else {
+        return Ok(0);
+    };
+
+    let mut diff = Diff::default();
+    let mut seen: HashSet<i32> = HashSet::new();
+    let mut batch: Vec<TypeRecord> = Vec::with_capacity(BATCH_SIZE);
+    let mut progress = Progress::unbounded("types written");
+    let mut imported = 0;
+
+    while let Some(entry) = entries.recv().await {
+        let (type_id, type_data) = entry?;
+        seen.insert(type_id);
+        batch.push(TypeRecord::from_sde(type_id, type_data));
+
+        if batch.len() == BATCH_SIZE {
+            imported += write_type_batch(conn, &mut batch, &mut diff, &mut progress).await?;
+        }
+    }
+    imported += write_type_batch(conn, &mut batch, &mut diff, &mut progress).await?;
+    progress.finish();
+
+    // Types stored by earlier SDE imports that this SDE no longer contains
+    let stored: Vec<i32> = sqlx::query_scalar("SELECT type_id FROM eve_types WHERE from_sde = 1")
+        .fetch_all(&mut *conn)
+        .await?;
+    diff.removed = stored.into_iter().filter(|id| !seen.contains(id)).collect();
+    diff.added.sort_unstable();
+    diff.changed.sort_unstable();
+    diff.removed.sort_unstable();
+
+    println!("Found {} type definitions", seen.len());
+    println!("Type changes: {}", diff);
+
+    let removed = remove_types(conn, &diff.removed).await?;
+    diff.record(conn, ctx.run_id, "type").await?;
+
+    println!("✓ Imported {} type IDs ({} removed)", imported, removed);
+
+    let backfilled = backfill_type_categories(conn).await?;
+    println!("✓ Backfilled category for {} types\n", backfilled);
+
+    Ok(imported + removed)
+}
+
+/// Compare a batch of streamed types with what earlier SDE imports stored and write the
+/// added and changed ones, draining the batch
+async fn write_type_batch(
+    conn: &mut SqliteConnection,
+    batch: &mut Vec<TypeRecord>,
+    diff: &mut Diff,
+    progress: &mut Progress,
+) -> Result<u64> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT type_id, name, description, group_id, volume, base_price, market_group_id, portion_size
+         FROM eve_types WHERE from_sde = 1 AND type_id IN (",
+    );
+    let mut separated = query.separated(", ");
+    for record in batch.iter() {
+        separated.push_bind(record.type_id);
+    }
+    query.push(")");
+
+    let mut current: HashMap<i32, TypeRecord> = query
+        .build_query_as::<TypeRecord>()
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|t| (t.type_id, t))
+        .collect();
+
+    let mut query = QueryBuilder::<Sqlite>::new(
+        "SELECT type_id, language, name, description FROM eve_type_names
+         WHERE type_id IN (",
+    );
+    let mut separated = query.separated(", ");
+    for type_id in current.keys() {
+        separated.push_bind(*type_id);
+    }
+    query.push(") ORDER BY type_id, language");
+
+    if !current.is_empty() {
+        let stored: Vec<(i32, String, String, Option<String>)> =
+            query.build_query_as().fetch_all(&mut *conn).await?;
+        for (type_id, language, name, description) in stored {
+            if let Some(record) = current.get_mut(&type_id) {
+                record.translations.push((language, name, description));
+            }
+        }
+    }
+
+    let rows: Vec<TypeRecord> = batch
+        .drain(..)
+        .filter(|record| diff.classify(record.type_id, current.get(&record.type_id), record))
+        .collect();
+
+    let written = insert_batched(
+        conn,
+        "INSERT INTO eve_types
+         (type_id, name, description, group_id, volume, base_price, market_group_id, portion_size, from_sde)",
+        "ON CONFLICT(type_id) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            group_id = excluded.group_id,
+            volume = excluded.volume,
+            base_price = excluded.base_price,
+            market_group_id = excluded.market_group_id,
+            portion_size = excluded.portion_size,
+            from_sde = 1",
+        &rows,
+        progress,
+        |b, t| {
+            b.push_bind(t.type_id)
+                .push_bind(t.name.as_str())
+                .push_bind(t.description.as_deref())
+                .push_bind(t.group_id)
+                .push_bind(t.volume)
+                .push_bind(t.base_price)
+                .push_bind(t.market_group_id)
+                .push_bind(t.portion_size)
+                .push_bind(true);
+        },
+    )
+    .await?;
+
+    // Replace every language of the written types
+    let type_ids: Vec<i32> = rows.iter().map(|t| t.type_id).collect();
+    execute_for_ids(conn, "DELETE FROM eve_type_names WHERE type_id IN ({ids})", &type_ids).await?;
+
+    let names: Vec<(i32, &str, &str, Option<&str>)> = rows
+        .iter()
+        .flat_map(|t| {
+            t.translations.iter().map(|(language, name, description)| {
+                (t.type_id, language.as_str(), name.as_str(), description.as_deref())
+            })
+        })
+        .collect();
+
+    insert_batched(
+        conn,
+        "INSERT INTO eve_type_names (type_id, language, name, description)",
+        "",
+        &names,
+        &mut Progress::silent(),
+        |b, (type_id, language, name, description)| {
+            b.push_bind(*type_id)
+                .push_bind(*language)
+                .push_bind(*name)
+                .push_bind(*description);
+        },
+    )
+    .await?;
+
+    Ok(written)
+}
+
+/// Type fields owned by the SDE, compared between imports
+#[derive(Debug, PartialEq, sqlx::FromRow)]
+struct TypeRecord {
+    type_id: i32,
+    name: String,
+    description: Option<String>,
+    group_id: Option<i32>,
+    volume: Option<f64>,
+    base_price: Option<f64>,
+    market_group_id: Option<i32>,
+    portion_size: Option<i32>,
+    /// (language, name, description) for every localized name, sorted by language
+    #[sqlx(skip)]
+    translations: Vec<(String, String, Option<String>)>,
+}
+
+impl TypeRecord {
+    fn from_sde(type_id: i32, type_data: TypeId) -> Self {
+        let description = type_data.description
+            .as_ref()
+            .and_then(|d| d.get("en"))
+            .map(|s| s.to_string());
+
+        let mut translations: Vec<(String, String, Option<String>)> = type_data.name
+            .iter()
+            .map(|(language, name)| {
+                let description = type_data.description
+                    .as_ref()
+                    .and_then(|d| d.get(language))
+                    .cloned();
+                (language.clone(), name.clone(), description)
+            })
+            .collect();
+        translations.sort_unstable();
+
+        Self {
+            type_id,
+            name: english_name(&type_data.name, type_id),
+            description,
+            group_id: type_data.group_id,
+            volume: type_data.volume,
+            base_price: type_data.base_price,
+            market_group_id: type_data.market_group_id,
+            portion_size: type_data.portion_size,
+            translations,
+        }
+    }
+}
+
+/// Delete types that are no longer in the SDE, along with everything that references them
+async fn remove_types(conn: &mut SqliteConnection, type_ids: &[i32]) -> Result<u64> {
+    if type_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let dependents = [
+        "DELETE FROM market_prices WHERE type_id IN ({ids})",
+        "DELETE FROM market_price_history WHERE type_id IN ({ids})",
+        "DELETE FROM eve_type_names WHERE type_id IN ({ids})",
+        "DELETE FROM material_reprocessing WHERE source_type_id IN ({ids}) OR material_type_id IN ({ids})",
+        "DELETE FROM blueprint_materials WHERE material_type_id IN ({ids}) OR blueprint_type_id IN ({ids})",
+        "DELETE FROM blueprint_materials WHERE blueprint_type_id IN
+            (SELECT blueprint_type_id FROM blueprints WHERE product_type_id IN ({ids}))",
+        "DELETE FROM blueprint_skills WHERE skill_type_id IN ({ids}) OR blueprint_type_id IN ({ids})",
+        "DELETE FROM blueprint_skills WHERE blueprint_type_id IN
+            (SELECT blueprint_type_id FROM blueprints WHERE product_type_id IN ({ids}))",
+        "DELETE FROM reprocessing_skills WHERE source_type_id IN ({ids}) OR skill_type_id IN ({ids})",
+        "DELETE FROM blueprints WHERE blueprint_type_id IN ({ids}) OR product_type_id IN ({ids})",
+        "DELETE FROM blueprint_activity_materials WHERE blueprint_type_id IN ({ids}) OR material_type_id IN ({ids})",
+        "DELETE FROM blueprint_activity_products WHERE blueprint_type_id IN ({ids}) OR product_type_id IN ({ids})",
+        "DELETE FROM blueprint_activity_skills WHERE blueprint_type_id IN ({ids}) OR skill_type_id IN ({ids})",
+        "DELETE FROM blueprint_activities WHERE blueprint_type_id IN ({ids})",
+        "DELETE FROM type_attributes WHERE type_id IN ({ids})",
+        "DELETE FROM npc_types WHERE type_id IN ({ids})",
+        "DELETE FROM wreck_salvage WHERE salvage_type_id IN ({ids})",
+    ];
+    for sql in dependents {
+        execute_for_ids(conn, sql, type_ids).await?;
+    }
+
+    execute_for_ids(conn, "DELETE FROM eve_types WHERE type_id IN ({ids})", type_ids).await
+}
+
+/// Set eve_types.category_id from each type's group
+async fn backfill_type_categories(conn: &mut SqliteConnection) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE eve_types
+         SET category_id = (SELECT category_id FROM eve_groups WHERE group_id = eve_types.group_id)
+         WHERE group_id IN (SELECT group_id FROM eve_groups)"
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,397 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sqlx::sqlite::SqliteConnection;
+use std::collections::{HashMap, HashSet};
+
+use crate::db::{execute_for_ids, insert_batched};
+use crate::progress::Progress;
+use crate::sde::{stream_list, UNIVERSE_DIR};
+use crate::ImportContext;
+
+#[derive(Debug, Deserialize)]
+struct RegionData {
+    #[serde(rename = "regionID")]
+    region_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConstellationData {
+    #[serde(rename = "constellationID")]
+    constellation_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolarSystemData {
+    #[serde(rename = "solarSystemID")]
+    solar_system_id: i32,
+    security: f64,
+    stargates: Option<HashMap<i32, StargateData>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StargateData {
+    destination: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct StationData {
+    #[serde(rename = "stationID")]
+    station_id: i32,
+    #[serde(rename = "stationName")]
+    station_name: String,
+    #[serde(rename = "solarSystemID")]
+    solar_system_id: i32,
+    #[serde(rename = "stationTypeID")]
+    station_type_id: Option<i32>,
+    #[serde(rename = "reprocessingEfficiency")]
+    reprocessing_efficiency: Option<f64>,
+    #[serde(rename = "reprocessingStationsTake")]
+    reprocessing_stations_take: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InvName {
+    #[serde(rename = "itemID")]
+    item_id: i64,
+    /// Mostly strings, but YAML reads names like `0` as numbers
+    #[serde(rename = "itemName")]
+    item_name: serde_yaml::Value,
+}
+
+/// A solar system read from the universe tree, before names are attached
+struct SystemRecord {
+    solar_system_id: i32,
+    constellation_id: i32,
+    region_id: i32,
+    security: f64,
+    /// (stargate_id, destination_stargate_id)
+    stargates: Vec<(i32, i32)>,
+}
+
+/// Directory of a `/`-separated SDE path
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+pub async fn import_universe(conn: &mut SqliteConnection, ctx: &ImportContext<'_>) -> Result<u64> {
+    println!("Importing universe map...");
+
+    // fsd/universe/<space>/<Region>/<Constellation>/<System>/solarsystem.staticdata;
+    // parents are only identified by the directory they live in
+    let files: Vec<String> = ctx.sde
+        .list_files(UNIVERSE_DIR)?
+        .into_iter()
+        .filter(|f| {
+            f.ends_with("/region.staticdata")
+                || f.ends_with("/constellation.staticdata")
+                || f.ends_with("/solarsystem.staticdata")
+        })
+        .collect();
+
+    if files.is_empty() {
+        println!("⚠️  {} not found, skipping\n", UNIVERSE_DIR);
+        return Ok(0);
+    }
+
+    let mut region_dirs: HashMap<String, i32> = HashMap::new();
+    // constellation directory -> (constellation_id, region directory)
+    let mut constellation_dirs: HashMap<String, (i32, String)> = HashMap::new();
+    // (system directory, data)
+    let mut system_files: Vec<(String, SolarSystemData)> = Vec::new();
+
+    let mut progress = Progress::new("universe files", files.len());
+    ctx.sde.for_each_file(&files, |path, reader| {
+        let dir = parent_dir(path).to_string();
+
+        if path.ends_with("/region.staticdata") {
+            let region: RegionData = serde_yaml::from_reader(reader)
+                .with_context(|| format!("Failed to parse {}", path))?;
+            region_dirs.insert(dir, region.region_id);
+        } else if path.ends_with("/constellation.staticdata") {
+            let constellation: ConstellationData = serde_yaml::from_reader(reader)
+                .with_context(|| format!("Failed to parse {}", path))?;
+            let region_dir = parent_dir(&dir).to_string();
+            constellation_dirs.insert(dir, (constellation.constellation_id, region_dir));
+        } else {
+            let system: SolarSystemData = serde_yaml::from_reader(reader)
+                .with_context(|| format!("Failed to parse {}", path))?;
+            system_files.push((dir, system));
+        }
+
+        progress.advance(1);
+        Ok(())
+    })?;
+    progress.finish();
+
+    let constellations: Vec<(i32, i32)> = constellation_dirs
+        .values()
+        .filter_map(|(constellation_id, region_dir)| {
+            region_dirs.get(region_dir).map(|region_id| (*constellation_id, *region_id))
+        })
+        .collect();
+    let constellation_regions: HashMap<&str, (i32, i32)> = constellation_dirs
+        .iter()
+        .filter_map(|(dir, (constellation_id, region_dir))| {
+            region_dirs
+                .get(region_dir)
+                .map(|region_id| (dir.as_str(), (*constellation_id, *region_id)))
+        })
+        .collect();
+
+    let systems: Vec<SystemRecord> = system_files
+        .into_iter()
+        .filter_map(|(dir, system)| {
+            let (constellation_id, region_id) = *constellation_regions.get(parent_dir(&dir))?;
+            let mut stargates: Vec<(i32, i32)> = system.stargates
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(stargate_id, gate)| (stargate_id, gate.destination))
+                .collect();
+            stargates.sort_unstable();
+
+            Some(SystemRecord {
+                solar_system_id: system.solar_system_id,
+                constellation_id,
+                region_id,
+                security: system.security,
+                stargates,
+            })
+        })
+        .collect();
+
+    println!(
+        "Found {} regions, {} constellations, {} solar systems",
+        region_dirs.len(),
+        constellations.len(),
+        systems.len()
+    );
+
+    // Region, constellation and system names live in invNames
+    let mut wanted: HashSet<i64> = region_dirs.values().map(|id| *id as i64).collect();
+    wanted.extend(constellations.iter().map(|(id, _)| *id as i64));
+    wanted.extend(systems.iter().map(|s| s.solar_system_id as i64));
+    let names = load_names(ctx, &wanted).await?;
+    let name_of = |id: i32| {
+        names.get(&(id as i64)).cloned().unwrap_or_else(|| format!("Unknown {}", id))
+    };
+
+    let regions: Vec<(i32, String)> = region_dirs.values().map(|id| (*id, name_of(*id))).collect();
+    let mut progress = Progress::new("regions", regions.len());
+    let mut imported = insert_batched(
+        conn,
+        "INSERT INTO regions (region_id, name)",
+        "ON CONFLICT(region_id) DO UPDATE SET name = excluded.name",
+        &regions,
+        &mut progress,
+        |b, (region_id, name)| {
+            b.push_bind(*region_id).push_bind(name.as_str());
+        },
+    )
+    .await?;
+    progress.finish();
+
+    let constellation_rows: Vec<(i32, i32, String)> = constellations
+        .iter()
+        .map(|(constellation_id, region_id)| (*constellation_id, *region_id, name_of(*constellation_id)))
+        .collect();
+    let mut progress = Progress::new("constellations", constellation_rows.len());
+    imported += insert_batched(
+        conn,
+        "INSERT INTO constellations (constellation_id, region_id, name)",
+        "ON CONFLICT(constellation_id) DO UPDATE SET
+            region_id = excluded.region_id,
+            name = excluded.name",
+        &constellation_rows,
+        &mut progress,
+        |b, (constellation_id, region_id, name)| {
+            b.push_bind(*constellation_id).push_bind(*region_id).push_bind(name.as_str());
+        },
+    )
+    .await?;
+    progress.finish();
+
+    let system_rows: Vec<(&SystemRecord, String)> = systems
+        .iter()
+        .map(|s| (s, name_of(s.solar_system_id)))
+        .collect();
+    let mut progress = Progress::new("solar systems", system_rows.len());
+    imported += insert_batched(
+        conn,
+        "INSERT INTO solar_systems (solar_system_id, constellation_id, region_id, name, security)",
+        "ON CONFLICT(solar_system_id) DO UPDATE SET
+            constellation_id = excluded.constellation_id,
+            region_id = excluded.region_id,
+            name = excluded.name,
+            security = excluded.security",
+        &system_rows,
+        &mut progress,
+        |b, (system, name)| {
+            b.push_bind(system.solar_system_id)
+                .push_bind(system.constellation_id)
+                .push_bind(system.region_id)
+                .push_bind(name.as_str())
+                .push_bind(system.security);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    // Jump graph: resolve each gate's destination gate to the system it sits in
+    let gate_systems: HashMap<i32, i32> = systems
+        .iter()
+        .flat_map(|s| s.stargates.iter().map(|(stargate_id, _)| (*stargate_id, s.solar_system_id)))
+        .collect();
+    let stargates: Vec<(i32, i32, i32, i32)> = systems
+        .iter()
+        .flat_map(|s| {
+            s.stargates.iter().filter_map(|(stargate_id, destination)| {
+                let destination_system_id = gate_systems.get(destination)?;
+                Some((*stargate_id, s.solar_system_id, *destination, *destination_system_id))
+            })
+        })
+        .collect();
+
+    // The gate network is rebuilt from scratch every import; nothing references it
+    sqlx::query("DELETE FROM stargates").execute(&mut *conn).await?;
+    let mut progress = Progress::new("stargates", stargates.len());
+    imported += insert_batched(
+        conn,
+        "INSERT INTO stargates (stargate_id, solar_system_id, destination_stargate_id, destination_system_id)",
+        "",
+        &stargates,
+        &mut progress,
+        |b, (stargate_id, solar_system_id, destination_stargate_id, destination_system_id)| {
+            b.push_bind(*stargate_id)
+                .push_bind(*solar_system_id)
+                .push_bind(*destination_stargate_id)
+                .push_bind(*destination_system_id);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    imported += import_stations(conn, ctx, &systems).await?;
+
+    // Drop what is no longer in the SDE, children first; stations in removed systems go with them
+    let system_ids: HashSet<i32> = systems.iter().map(|s| s.solar_system_id).collect();
+    let removed_systems = missing_ids(conn, "solar_systems", "solar_system_id", &system_ids).await?;
+    execute_for_ids(conn, "DELETE FROM stations WHERE solar_system_id IN ({ids})", &removed_systems).await?;
+    execute_for_ids(conn, "DELETE FROM solar_systems WHERE solar_system_id IN ({ids})", &removed_systems).await?;
+
+    let constellation_ids: HashSet<i32> = constellations.iter().map(|(id, _)| *id).collect();
+    let removed_constellations = missing_ids(conn, "constellations", "constellation_id", &constellation_ids).await?;
+    execute_for_ids(conn, "DELETE FROM constellations WHERE constellation_id IN ({ids})", &removed_constellations).await?;
+
+    let region_ids: HashSet<i32> = region_dirs.values().copied().collect();
+    let removed_regions = missing_ids(conn, "regions", "region_id", &region_ids).await?;
+    execute_for_ids(conn, "DELETE FROM regions WHERE region_id IN ({ids})", &removed_regions).await?;
+
+    let removed = removed_regions.len() + removed_constellations.len() + removed_systems.len();
+    if removed > 0 {
+        println!("Removed {} regions, constellations and solar systems no longer in the SDE", removed);
+    }
+
+    println!("✓ Imported universe map ({} stargates)\n", stargates.len());
+    Ok(imported)
+}
+
+/// Load invNames entries for the given item IDs, streaming the (very large) file
+async fn load_names(ctx: &ImportContext<'_>, wanted: &HashSet<i64>) -> Result<HashMap<i64, String>> {
+    let mut names = HashMap::new();
+
+    let Some(mut entries) = stream_list::<InvName>(ctx.sde, "bsd/invNames.yaml") else {
+        return Ok(names);
+    };
+
+    while let Some(entry) = entries.recv().await {
+        let entry = entry?;
+        if !wanted.contains(&entry.item_id) {
+            continue;
+        }
+
+        let name = match entry.item_name {
+            serde_yaml::Value::String(name) => name,
+            other => serde_yaml::to_string(&other)?.trim().to_string(),
+        };
+        names.insert(entry.item_id, name);
+    }
+
+    Ok(names)
+}
+
+/// Ids in `table` that are not in `keep`
+async fn missing_ids(
+    conn: &mut SqliteConnection,
+    table: &str,
+    id_column: &str,
+    keep: &HashSet<i32>,
+) -> Result<Vec<i32>> {
+    let ids: Vec<i32> = sqlx::query_scalar(&format!("SELECT {} FROM {}", id_column, table))
+        .fetch_all(conn)
+        .await?;
+
+    Ok(ids.into_iter().filter(|id| !keep.contains(id)).collect())
+}
+
+/// Import NPC stations, streaming staStations.yaml, and drop stations no longer in it
+async fn import_stations(
+    conn: &mut SqliteConnection,
+    ctx: &ImportContext<'_>,
+    systems: &[SystemRecord],
+) -> Result<u64> {
+    let Some(mut entries) = stream_list::<StationData>(ctx.sde, "bsd/staStations.yaml") else {
+        return Ok(0);
+    };
+
+    let known_systems: HashSet<i32> = systems.iter().map(|s| s.solar_system_id).collect();
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+
+    while let Some(entry) = entries.recv().await {
+        let station = entry?;
+        if known_systems.contains(&station.solar_system_id) {
+            rows.push(station);
+        } else {
+            skipped += 1;
+        }
+    }
+
+    let mut progress = Progress::new("stations", rows.len());
+    let imported = insert_batched(
+        conn,
+        "INSERT INTO stations
+         (station_id, solar_system_id, name, station_type_id, reprocessing_efficiency, reprocessing_stations_take)",
+        "ON CONFLICT(station_id) DO UPDATE SET
+            solar_system_id = excluded.solar_system_id,
+            name = excluded.name,
+            station_type_id = excluded.station_type_id,
+            reprocessing_efficiency = excluded.reprocessing_efficiency,
+            reprocessing_stations_take = excluded.reprocessing_stations_take",
+        &rows,
+        &mut progress,
+        |b, station| {
+            b.push_bind(station.station_id)
+                .push_bind(station.solar_system_id)
+                .push_bind(station.station_name.as_str())
+                .push_bind(station.station_type_id)
+                .push_bind(station.reprocessing_efficiency)
+                .push_bind(station.reprocessing_stations_take);
+        },
+    )
+    .await?;
+    progress.finish();
+
+    let station_ids: HashSet<i32> = rows.iter().map(|station| station.station_id).collect();
+    let removed = missing_ids(conn, "stations", "station_id", &station_ids).await?;
+    execute_for_ids(conn, "DELETE FROM stations WHERE station_id IN ({ids})", &removed).await?;
+
+    println!("✓ Imported {} NPC stations", rows.len());
+    if !removed.is_empty() {
+        println!("Removed {} stations no longer in the SDE", removed.len());
+    }
+    if skipped > 0 {
+        println!("⚠️  Skipped {} stations in unknown solar systems", skipped);
+    }
+
+    Ok(imported)
+}
@@ -0,0 +1,291 @@
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::sqlite::SqlitePool;
+use std::path::Path;
+
+/// Offending rows listed per check in the report; the count always covers all of them
+const MAX_EXAMPLES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Broken references; the data cannot be used as-is
+    Error,
+    /// Ambiguous or incomplete data that lookups cope with, but may get wrong
+    Warning,
+}
+
+/// A consistency check: `sql` selects one TEXT `detail` per offending row
+struct Check {
+    name: &'static str,
+    severity: Severity,
+    description: &'static str,
+    sql: &'static str,
+}
+
+const CHECKS: &[Check] = &[
+    Check {
+        name: "blueprint_missing_type",
+        severity: Severity::Error,
+        description: "Blueprints whose blueprint type is not in eve_types",
+        sql: "SELECT 'blueprint ' || b.blueprint_type_id FROM blueprints b
+              WHERE b.blueprint_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "blueprint_missing_product",
+        severity: Severity::Error,
+        description: "Blueprints whose product is not in eve_types",
+        sql: "SELECT 'blueprint ' || b.blueprint_type_id || ' -> product ' || b.product_type_id FROM blueprints b
+              WHERE b.product_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "blueprint_material_missing_type",
+        severity: Severity::Error,
+        description: "Blueprint materials whose blueprint or material type is not in eve_types",
+        sql: "SELECT 'blueprint ' || m.blueprint_type_id || ' -> material ' || m.material_type_id
+              FROM blueprint_materials m
+              WHERE m.blueprint_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR m.material_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "blueprint_material_missing_blueprint",
+        severity: Severity::Error,
+        description: "Blueprint materials for blueprints that are not in blueprints",
+        sql: "SELECT 'blueprint ' || m.blueprint_type_id || ' -> material ' || m.material_type_id
+              FROM blueprint_materials m
+              WHERE m.blueprint_type_id NOT IN (SELECT blueprint_type_id FROM blueprints)",
+    },
+    Check {
+        name: "blueprint_skill_missing_type",
+        severity: Severity::Error,
+        description: "Blueprint skill requirements whose blueprint or skill is unknown",
+        sql: "SELECT 'blueprint ' || s.blueprint_type_id || ' -> skill ' || s.skill_type_id
+              FROM blueprint_skills s
+              WHERE s.blueprint_type_id NOT IN (SELECT blueprint_type_id FROM blueprints)
+                 OR s.skill_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "blueprint_activity_missing_type",
+        severity: Severity::Error,
+        description: "Blueprint activity materials, products or skills referencing types not in eve_types",
+        sql: "SELECT 'blueprint ' || blueprint_type_id || ' ' || activity || ' -> material ' || material_type_id
+              FROM blueprint_activity_materials
+              WHERE blueprint_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR material_type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'blueprint ' || blueprint_type_id || ' ' || activity || ' -> product ' || product_type_id
+              FROM blueprint_activity_products
+              WHERE blueprint_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR product_type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'blueprint ' || blueprint_type_id || ' ' || activity || ' -> skill ' || skill_type_id
+              FROM blueprint_activity_skills
+              WHERE blueprint_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR skill_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "reprocessing_missing_type",
+        severity: Severity::Error,
+        description: "Reprocessing yields whose source item or material is not in eve_types",
+        sql: "SELECT 'source ' || r.source_type_id || ' -> material ' || r.material_type_id
+              FROM material_reprocessing r
+              WHERE r.source_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR r.material_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "reprocessing_skill_missing_type",
+        severity: Severity::Error,
+        description: "Processing skills whose item or skill is not in eve_types",
+        sql: "SELECT 'source ' || s.source_type_id || ' -> skill ' || s.skill_type_id
+              FROM reprocessing_skills s
+              WHERE s.source_type_id NOT IN (SELECT type_id FROM eve_types)
+                 OR s.skill_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "type_row_missing_type",
+        severity: Severity::Error,
+        description: "Names, attributes, market prices or NPC entries for types not in eve_types",
+        sql: "SELECT 'eve_type_names ' || type_id || ' (' || language || ')' FROM eve_type_names
+              WHERE type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'type_attributes ' || type_id FROM type_attributes
+              WHERE type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'market_prices ' || type_id FROM market_prices
+              WHERE type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT DISTINCT 'market_price_history ' || type_id FROM market_price_history
+              WHERE type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'npc_types ' || type_id FROM npc_types
+              WHERE type_id NOT IN (SELECT type_id FROM eve_types)
+              UNION ALL
+              SELECT 'wreck_salvage ' || faction || '/' || wreck_size || ' -> ' || salvage_type_id FROM wreck_salvage
+              WHERE salvage_type_id NOT IN (SELECT type_id FROM eve_types)",
+    },
+    Check {
+        name: "universe_missing_parent",
+        severity: Severity::Error,
+        description: "Constellations, solar systems, stargates or stations whose parent is missing",
+        sql: "SELECT 'constellation ' || constellation_id || ' -> region ' || region_id FROM constellations
+              WHERE region_id NOT IN (SELECT region_id FROM regions)
+              UNION ALL
+              SELECT 'solar system ' || solar_system_id || ' -> constellation ' || constellation_id FROM solar_systems
+              WHERE constellation_id NOT IN (SELECT constellation_id FROM constellations)
+                 OR region_id NOT IN (SELECT region_id FROM regions)
+              UNION ALL
+              SELECT 'stargate ' || stargate_id || ' -> system ' || destination_system_id FROM stargates
+              WHERE solar_system_id NOT IN (SELECT solar_system_id FROM solar_systems)
+                 OR destination_system_id NOT IN (SELECT solar_system_id FROM solar_systems)
+              UNION ALL
+              SELECT 'station ' || station_id || ' -> system ' || solar_system_id FROM stations
+              WHERE solar_system_id NOT IN (SELECT solar_system_id FROM solar_systems)",
+    },
+    Check {
+        name: "type_unknown_group",
+        severity: Severity::Warning,
+        description: "Types whose group is not in eve_groups (no group/category labels or filters)",
+        sql: "SELECT 'type ' || t.type_id || ' -> group ' || t.group_id FROM eve_types t
+              WHERE t.group_id IS NOT NULL
+                AND EXISTS (SELECT 1 FROM eve_groups)
+                AND t.group_id NOT IN (SELECT group_id FROM eve_groups)",
+    },
+    Check {
+        name: "blueprint_without_materials",
+        severity: Severity::Warning,
+        description: "Blueprints with no manufacturing materials (never matched against salvage)",
+        sql: "SELECT 'blueprint ' || b.blueprint_type_id FROM blueprints b
+              WHERE NOT EXISTS (SELECT 1 FROM blueprint_materials m WHERE m.blueprint_type_id = b.blueprint_type_id)",
+    },
+    Check {
+        name: "duplicate_type_name",
+        severity: Severity::Warning,
+        description: "English names shared by several types; name lookups pick only one of them",
+        sql: "SELECT name || ' (' || COUNT(*) || ' types: ' || GROUP_CONCAT(type_id, ', ') || ')' FROM eve_types
+              GROUP BY name HAVING COUNT(*) > 1
+              ORDER BY name",
+    },
+    Check {
+        name: "ambiguous_translated_name",
+        severity: Severity::Warning,
+        description: "Translated names shared by several types and not any type's English name; name lookups pick only one of them",
+        sql: "SELECT name || ' (' || COUNT(DISTINCT type_id) || ' types: ' || GROUP_CONCAT(DISTINCT type_id) || ')'
+              FROM eve_type_names
+              WHERE name NOT IN (SELECT name FROM eve_types)
+              GROUP BY name HAVING COUNT(DISTINCT type_id) > 1
+              ORDER BY name",
+    },
+];
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+    pub count: usize,
+    /// Up to MAX_EXAMPLES offending rows
+    pub examples: Vec<String>,
+}
+
+/// Machine-readable validation report (written as JSON with `--report`)
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub generated_at: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub checks: Vec<CheckResult>,
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+
+    /// Print a human-readable summary
+    pub fn print(&self) {
+        for check in &self.checks {
+            let mark = match (check.count, check.severity) {
+                (0, _) => "✓",
+                (_, Severity::Error) => "✗",
+                (_, Severity::Warning) => "⚠️ ",
+            };
+            println!("{} {:<38} {}", mark, check.name, check.count);
+
+            if check.count > 0 {
+                println!("    {}", check.description);
+                for example in &check.examples {
+                    println!("    - {}", example);
+                }
+                if check.count > check.examples.len() {
+                    println!("    ... and {} more", check.count - check.examples.len());
+                }
+            }
+        }
+
+        println!("\n{} errors, {} warnings", self.errors, self.warnings);
+    }
+
+    /// Write the report as JSON
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Run every consistency check against the imported data
+pub async fn validate(pool: &SqlitePool) -> Result<ValidationReport> {
+    let mut checks = Vec::with_capacity(CHECKS.len());
+
+    for check in CHECKS {
+        let mut details: Vec<String> = sqlx::query_scalar(check.sql).fetch_all(pool).await?;
+        let count = details.len();
+        details.truncate(MAX_EXAMPLES);
+
+        checks.push(CheckResult {
+            name: check.name,
+            severity: check.severity,
+            description: check.description,
+            count,
+            examples: details,
+        });
+    }
+
+    let failing = |severity| {
+        checks.iter().filter(|c| c.severity == severity && c.count > 0).count()
+    };
+
+    Ok(ValidationReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        errors: failing(Severity::Error),
+        warnings: failing(Severity::Warning),
+        checks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use salvo_backend::test_support;
+
+    #[tokio::test]
+    async fn test_validate_reports_broken_references() {
+        let pool = test_support::pool().await;
+
+        let report = validate(&pool).await.unwrap();
+        assert!(!report.has_errors(), "seed data should be consistent: {:?}", report.checks);
+
+        // Rows written without foreign key enforcement, as older import scripts did
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO material_reprocessing (source_type_id, material_type_id, quantity) VALUES (25595, 999999, 10)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = validate(&pool).await.unwrap();
+        let check = report.checks.iter().find(|c| c.name == "reprocessing_missing_type").unwrap();
+        assert_eq!(check.count, 1);
+        assert_eq!(check.examples, vec!["source 25595 -> material 999999"]);
+        assert!(report.has_errors());
+    }
+}
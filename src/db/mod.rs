@@ -1,10 +1,14 @@
 pub mod models;
 pub mod queries;
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::{sqlite::SqlitePool, Pool, Sqlite};
 
 pub type DbPool = Pool<Sqlite>;
 
+/// Format of SQLite's CURRENT_TIMESTAMP, used for every stored time (UTC)
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
 /// Initialize SQLite connection pool
 pub async fn init_pool() -> anyhow::Result<DbPool> {
     let database_url = std::env::var("DATABASE_URL")
@@ -23,3 +27,16 @@ pub async fn init_pool() -> anyhow::Result<DbPool> {
 
     Ok(pool)
 }
+
+/// Format a time as SQLite's CURRENT_TIMESTAMP does
+pub fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Read a CURRENT_TIMESTAMP-style time, or an RFC 3339 one
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+        .map(|time| time.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(text).map(|time| time.with_timezone(&Utc)))
+        .ok()
+}
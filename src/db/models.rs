@@ -14,6 +14,75 @@ pub struct EveType {
     pub portion_size: Option<i32>,
 }
 
+/// Type name and description in one language
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TypeName {
+    pub type_id: i32,
+    pub language: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Item category (e.g. Ship, Module, Charge)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EveCategory {
+    pub category_id: i32,
+    pub name: String,
+    pub published: bool,
+}
+
+/// Item group within a category (e.g. Rig Armor, Frigate)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EveGroup {
+    pub group_id: i32,
+    pub category_id: i32,
+    pub name: String,
+    pub published: bool,
+}
+
+/// Group and category names for a type
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TypeClassification {
+    pub type_id: i32,
+    pub group_name: Option<String>,
+    pub category_name: Option<String>,
+}
+
+/// Dogma attributes of a type (meta/tech level, rig size, calibration, fitting slot)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TypeAttributes {
+    pub type_id: i32,
+    pub meta_level: Option<i32>,
+    pub tech_level: Option<i32>,
+    /// 1 = small, 2 = medium, 3 = large, 4 = capital
+    pub rig_size: Option<i32>,
+    pub calibration: Option<i32>,
+    pub slot: Option<String>,
+}
+
+impl TypeAttributes {
+    /// Rig size as a label ("small", "medium", "large", "capital")
+    pub fn rig_size_name(&self) -> Option<&'static str> {
+        match self.rig_size? {
+            1 => Some("small"),
+            2 => Some("medium"),
+            3 => Some("large"),
+            4 => Some("capital"),
+            _ => None,
+        }
+    }
+}
+
+/// Market group (node in the in-game market browser tree)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MarketGroup {
+    pub market_group_id: i32,
+    pub parent_group_id: Option<i32>,
+    pub name: String,
+    pub description: Option<String>,
+    pub has_types: bool,
+}
+
 /// Material reprocessing yield
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct MaterialReprocessing {
@@ -30,6 +99,8 @@ pub struct Blueprint {
     pub product_type_id: i32,
     pub manufacturing_time: Option<i32>,
     pub max_production_limit: Option<i32>,
+    /// Units produced per run
+    pub product_quantity: i32,
 }
 
 /// Material requirement for a blueprint
@@ -41,19 +112,218 @@ pub struct BlueprintMaterial {
     pub quantity: i32,
 }
 
+/// Skill level required to manufacture from a blueprint
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BlueprintSkill {
+    pub blueprint_type_id: i32,
+    pub skill_type_id: i32,
+    pub level: i32,
+}
+
+/// Specific processing skill for reprocessing a type (e.g. Scrapmetal Processing)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ReprocessingSkill {
+    pub source_type_id: i32,
+    pub skill_type_id: i32,
+}
+
+/// Region of New Eden (e.g. The Forge)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Region {
+    pub region_id: i32,
+    pub name: String,
+}
+
+/// Constellation within a region
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Constellation {
+    pub constellation_id: i32,
+    pub region_id: i32,
+    pub name: String,
+}
+
+/// Solar system with its true security status
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SolarSystem {
+    pub solar_system_id: i32,
+    pub constellation_id: i32,
+    pub region_id: i32,
+    pub name: String,
+    pub security: f64,
+}
+
+/// NPC station
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Station {
+    pub station_id: i32,
+    pub solar_system_id: i32,
+    pub name: String,
+    pub station_type_id: Option<i32>,
+    pub reprocessing_efficiency: Option<f64>,
+    pub reprocessing_stations_take: Option<f64>,
+}
+
+/// Pirate NPC ship and the wreck it leaves
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NpcType {
+    pub type_id: i32,
+    pub name: String,
+    pub faction: String,
+    pub ship_class: String,
+    pub wreck_size: String,
+}
+
+/// Salvage a faction's wreck of a given size can yield
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WreckSalvage {
+    pub faction: String,
+    pub wreck_size: String,
+    pub salvage_type_id: i32,
+    pub salvage_name: String,
+    pub drop_chance: f64,
+    pub avg_quantity: f64,
+}
+
 /// Market price data
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct MarketPrice {
     pub type_id: i32,
-    pub region_id: i32,
+    /// "region" or "station"
+    pub location_type: String,
+    pub location_id: i64,
     pub sell_price: Option<f64>,
     pub buy_price: Option<f64>,
     pub daily_volume: Option<i32>,
     pub updated_at: String, // SQLite DATETIME as string
+    /// Highest buy order, when priced from an order book
+    pub best_bid: Option<f64>,
+    /// Lowest sell order, when priced from an order book
+    pub best_ask: Option<f64>,
+}
+
+/// One price refresh, from market_price_history
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MarketPriceHistory {
+    pub type_id: i32,
+    pub location_type: String,
+    pub location_id: i64,
+    pub sell_price: Option<f64>,
+    pub buy_price: Option<f64>,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub daily_volume: Option<i32>,
+    pub recorded_at: String,
+}
+
+/// Average of a day's price refreshes
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DailyPrice {
+    /// YYYY-MM-DD
+    pub day: String,
+    pub sell_price: Option<f64>,
+    pub buy_price: Option<f64>,
+    /// Refreshes recorded that day
+    pub samples: i64,
+}
+
+/// One run of the background price refresh
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PriceRefreshRun {
+    pub run_id: i64,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// "running", "success" or "failed"
+    pub status: String,
+    pub locations: i64,
+    pub updated_count: i64,
+    pub expires_at: Option<String>,
+    pub next_run_at: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Cached response of an external API GET
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct HttpCacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub expires_at: Option<String>,
+    /// Response headers as a JSON object
+    pub headers: String,
+    pub body: Vec<u8>,
+    pub fetched_at: String,
+}
+
+impl MarketPrice {
+    /// Region or station the price was taken at
+    pub fn location(&self) -> Option<MarketLocation> {
+        MarketLocation::from_parts(&self.location_type, self.location_id)
+    }
 }
 
 // ===== Working models for business logic =====
 
+/// Where market prices are taken: a whole region, one station, or everywhere
+///
+/// Serialized as `{"region": 10000002}`, `{"station": 60003760}` or `"global"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketLocation {
+    /// Every order in a region (e.g. 10000002, The Forge)
+    Region(i32),
+    /// Orders at one station (e.g. 60003760, Jita IV - Moon 4)
+    Station(i64),
+    /// Prices that are the same everywhere (e.g. ESI's average prices)
+    Global,
+}
+
+impl MarketLocation {
+    /// The Forge, where prices are cached unless another location is asked for
+    pub const THE_FORGE: MarketLocation = MarketLocation::Region(10000002);
+
+    /// Value stored in market_prices.location_type
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MarketLocation::Region(_) => "region",
+            MarketLocation::Station(_) => "station",
+            MarketLocation::Global => "global",
+        }
+    }
+
+    /// Value stored in market_prices.location_id
+    pub fn id(&self) -> i64 {
+        match self {
+            MarketLocation::Region(region_id) => *region_id as i64,
+            MarketLocation::Station(station_id) => *station_id,
+            MarketLocation::Global => 0,
+        }
+    }
+
+    /// Rebuild a location from its stored type and ID
+    pub fn from_parts(kind: &str, id: i64) -> Option<Self> {
+        match kind {
+            "region" => Some(MarketLocation::Region(id as i32)),
+            "station" => Some(MarketLocation::Station(id)),
+            "global" => Some(MarketLocation::Global),
+            _ => None,
+        }
+    }
+}
+
+impl Default for MarketLocation {
+    fn default() -> Self {
+        Self::THE_FORGE
+    }
+}
+
+impl std::fmt::Display for MarketLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketLocation::Global => write!(f, "global"),
+            _ => write!(f, "{} {}", self.kind(), self.id()),
+        }
+    }
+}
+
 /// Represents a material with quantity (for calculations)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Material {
@@ -1,17 +1,46 @@
 use super::models::*;
 use super::DbPool;
 use sqlx;
+use std::collections::HashSet;
 
-/// Look up EVE type by name
+/// Look up EVE type by name in any language (English matches take precedence)
 pub async fn get_type_by_name(pool: &DbPool, name: &str) -> sqlx::Result<Option<EveType>> {
     sqlx::query_as::<_, EveType>(
-        "SELECT * FROM eve_types WHERE name = ? LIMIT 1"
+        "SELECT * FROM eve_types
+         WHERE name = ?1
+            OR type_id IN (SELECT type_id FROM eve_type_names WHERE name = ?1)
+         ORDER BY name = ?1 DESC
+         LIMIT 1"
     )
     .bind(name)
     .fetch_optional(pool)
     .await
 }
 
+/// Get names of several types in one language; types without a translation are omitted
+pub async fn get_type_names(
+    pool: &DbPool,
+    type_ids: &[i32],
+    language: &str,
+) -> sqlx::Result<Vec<TypeName>> {
+    if type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT * FROM eve_type_names WHERE language = ? AND type_id IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, TypeName>(&query).bind(language);
+    for type_id in type_ids {
+        query = query.bind(type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
 /// Look up EVE type by type_id
 pub async fn get_type_by_id(pool: &DbPool, type_id: i32) -> sqlx::Result<Option<EveType>> {
     sqlx::query_as::<_, EveType>(
@@ -22,6 +51,327 @@ pub async fn get_type_by_id(pool: &DbPool, type_id: i32) -> sqlx::Result<Option<
     .await
 }
 
+/// Look up several EVE types by type_id
+pub async fn get_types_by_ids(pool: &DbPool, type_ids: &[i32]) -> sqlx::Result<Vec<EveType>> {
+    if type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!("SELECT * FROM eve_types WHERE type_id IN ({})", placeholders);
+
+    let mut query = sqlx::query_as::<_, EveType>(&query);
+    for type_id in type_ids {
+        query = query.bind(type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
+/// Which of `type_ids` exist in eve_types
+pub async fn get_existing_type_ids(pool: &DbPool, type_ids: &[i32]) -> sqlx::Result<HashSet<i32>> {
+    let mut existing = HashSet::new();
+
+    // Chunked to stay under SQLite's bound parameter limit on bulk backfills
+    for chunk in type_ids.chunks(900) {
+        let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!("SELECT type_id FROM eve_types WHERE type_id IN ({})", placeholders);
+
+        let mut query = sqlx::query_scalar::<_, i32>(&query);
+        for type_id in chunk {
+            query = query.bind(type_id);
+        }
+        existing.extend(query.fetch_all(pool).await?);
+    }
+
+    Ok(existing)
+}
+
+/// Get all item categories
+pub async fn get_categories(pool: &DbPool) -> sqlx::Result<Vec<EveCategory>> {
+    sqlx::query_as::<_, EveCategory>("SELECT * FROM eve_categories ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+/// Get all groups within a category
+pub async fn get_groups_for_category(
+    pool: &DbPool,
+    category_id: i32,
+) -> sqlx::Result<Vec<EveGroup>> {
+    sqlx::query_as::<_, EveGroup>(
+        "SELECT * FROM eve_groups WHERE category_id = ? ORDER BY name"
+    )
+    .bind(category_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get group and category names for multiple types
+pub async fn get_type_classifications(
+    pool: &DbPool,
+    type_ids: &[i32],
+) -> sqlx::Result<Vec<TypeClassification>> {
+    if type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT t.type_id, g.name AS group_name, c.name AS category_name
+         FROM eve_types t
+         LEFT JOIN eve_groups g ON g.group_id = t.group_id
+         LEFT JOIN eve_categories c ON c.category_id = t.category_id
+         WHERE t.type_id IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, TypeClassification>(&query);
+    for type_id in type_ids {
+        query = query.bind(type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
+/// Get dogma attributes for several types; types without any are omitted
+pub async fn get_type_attributes(
+    pool: &DbPool,
+    type_ids: &[i32],
+) -> sqlx::Result<Vec<TypeAttributes>> {
+    if type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!("SELECT * FROM type_attributes WHERE type_id IN ({})", placeholders);
+
+    let mut query = sqlx::query_as::<_, TypeAttributes>(&query);
+    for type_id in type_ids {
+        query = query.bind(type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
+/// Get all market groups
+pub async fn get_market_groups(pool: &DbPool) -> sqlx::Result<Vec<MarketGroup>> {
+    sqlx::query_as::<_, MarketGroup>("SELECT * FROM market_groups ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+/// Look up a market group by id
+pub async fn get_market_group(
+    pool: &DbPool,
+    market_group_id: i32,
+) -> sqlx::Result<Option<MarketGroup>> {
+    sqlx::query_as::<_, MarketGroup>(
+        "SELECT * FROM market_groups WHERE market_group_id = ? LIMIT 1"
+    )
+    .bind(market_group_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Get types in a market group, optionally including all descendant groups
+pub async fn get_types_in_market_group(
+    pool: &DbPool,
+    market_group_id: i32,
+    recursive: bool,
+) -> sqlx::Result<Vec<EveType>> {
+    if !recursive {
+        return sqlx::query_as::<_, EveType>(
+            "SELECT * FROM eve_types WHERE market_group_id = ? ORDER BY name"
+        )
+        .bind(market_group_id)
+        .fetch_all(pool)
+        .await;
+    }
+
+    sqlx::query_as::<_, EveType>(
+        "WITH RECURSIVE subtree(market_group_id) AS (
+            SELECT ?
+            UNION
+            SELECT mg.market_group_id FROM market_groups mg
+            JOIN subtree s ON mg.parent_group_id = s.market_group_id
+         )
+         SELECT * FROM eve_types
+         WHERE market_group_id IN (SELECT market_group_id FROM subtree)
+         ORDER BY name"
+    )
+    .bind(market_group_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get all regions
+pub async fn get_regions(pool: &DbPool) -> sqlx::Result<Vec<Region>> {
+    sqlx::query_as::<_, Region>("SELECT * FROM regions ORDER BY name")
+        .fetch_all(pool)
+        .await
+}
+
+/// Look up a region by id
+pub async fn get_region(pool: &DbPool, region_id: i32) -> sqlx::Result<Option<Region>> {
+    sqlx::query_as::<_, Region>("SELECT * FROM regions WHERE region_id = ? LIMIT 1")
+        .bind(region_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Look up a constellation by id
+pub async fn get_constellation(
+    pool: &DbPool,
+    constellation_id: i32,
+) -> sqlx::Result<Option<Constellation>> {
+    sqlx::query_as::<_, Constellation>(
+        "SELECT * FROM constellations WHERE constellation_id = ? LIMIT 1"
+    )
+    .bind(constellation_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Get all solar systems in a region
+pub async fn get_systems_in_region(pool: &DbPool, region_id: i32) -> sqlx::Result<Vec<SolarSystem>> {
+    sqlx::query_as::<_, SolarSystem>(
+        "SELECT * FROM solar_systems WHERE region_id = ? ORDER BY name"
+    )
+    .bind(region_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Look up a solar system by id
+pub async fn get_solar_system(
+    pool: &DbPool,
+    solar_system_id: i32,
+) -> sqlx::Result<Option<SolarSystem>> {
+    sqlx::query_as::<_, SolarSystem>(
+        "SELECT * FROM solar_systems WHERE solar_system_id = ? LIMIT 1"
+    )
+    .bind(solar_system_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Find solar systems whose name starts with `prefix` (case-insensitive)
+pub async fn search_solar_systems(
+    pool: &DbPool,
+    prefix: &str,
+    limit: i64,
+) -> sqlx::Result<Vec<SolarSystem>> {
+    sqlx::query_as::<_, SolarSystem>(
+        "SELECT * FROM solar_systems WHERE name LIKE ? || '%' ESCAPE '\\' ORDER BY name LIMIT ?"
+    )
+    .bind(prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the systems one stargate jump away from a system
+pub async fn get_adjacent_systems(
+    pool: &DbPool,
+    solar_system_id: i32,
+) -> sqlx::Result<Vec<SolarSystem>> {
+    sqlx::query_as::<_, SolarSystem>(
+        "SELECT * FROM solar_systems WHERE solar_system_id IN
+            (SELECT destination_system_id FROM stargates WHERE solar_system_id = ?)
+         ORDER BY name"
+    )
+    .bind(solar_system_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the NPC stations in a solar system
+pub async fn get_stations_in_system(
+    pool: &DbPool,
+    solar_system_id: i32,
+) -> sqlx::Result<Vec<Station>> {
+    sqlx::query_as::<_, Station>(
+        "SELECT * FROM stations WHERE solar_system_id = ? ORDER BY name"
+    )
+    .bind(solar_system_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Look up an NPC station by id
+pub async fn get_station(pool: &DbPool, station_id: i32) -> sqlx::Result<Option<Station>> {
+    sqlx::query_as::<_, Station>("SELECT * FROM stations WHERE station_id = ? LIMIT 1")
+        .bind(station_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Region of an NPC station
+pub async fn get_station_region(pool: &DbPool, station_id: i64) -> sqlx::Result<Option<i32>> {
+    sqlx::query_scalar(
+        "SELECT ss.region_id FROM stations s
+         JOIN solar_systems ss ON ss.solar_system_id = s.solar_system_id
+         WHERE s.station_id = ?"
+    )
+    .bind(station_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// List pirate NPC ships, optionally filtered by faction and wreck size
+pub async fn get_npc_types(
+    pool: &DbPool,
+    faction: Option<&str>,
+    wreck_size: Option<&str>,
+) -> sqlx::Result<Vec<NpcType>> {
+    sqlx::query_as::<_, NpcType>(
+        "SELECT n.type_id, t.name, n.faction, n.ship_class, n.wreck_size
+         FROM npc_types n
+         JOIN eve_types t ON n.type_id = t.type_id
+         WHERE (?1 IS NULL OR n.faction = ?1)
+           AND (?2 IS NULL OR n.wreck_size = ?2)
+         ORDER BY n.faction, t.name"
+    )
+    .bind(faction)
+    .bind(wreck_size)
+    .fetch_all(pool)
+    .await
+}
+
+/// Look up a pirate NPC ship by type id
+pub async fn get_npc_type(pool: &DbPool, type_id: i32) -> sqlx::Result<Option<NpcType>> {
+    sqlx::query_as::<_, NpcType>(
+        "SELECT n.type_id, t.name, n.faction, n.ship_class, n.wreck_size
+         FROM npc_types n
+         JOIN eve_types t ON n.type_id = t.type_id
+         WHERE n.type_id = ?"
+    )
+    .bind(type_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Get the salvage a faction's wreck of the given size can yield
+pub async fn get_wreck_salvage(
+    pool: &DbPool,
+    faction: &str,
+    wreck_size: &str,
+) -> sqlx::Result<Vec<WreckSalvage>> {
+    sqlx::query_as::<_, WreckSalvage>(
+        "SELECT w.faction, w.wreck_size, w.salvage_type_id, t.name AS salvage_name,
+                w.drop_chance, w.avg_quantity
+         FROM wreck_salvage w
+         JOIN eve_types t ON w.salvage_type_id = t.type_id
+         WHERE w.faction = ? AND w.wreck_size = ?
+         ORDER BY w.drop_chance * w.avg_quantity DESC"
+    )
+    .bind(faction)
+    .bind(wreck_size)
+    .fetch_all(pool)
+    .await
+}
+
 /// Get reprocessing yields for a salvage item
 pub async fn get_reprocessing_yields(
     pool: &DbPool,
@@ -68,6 +418,52 @@ pub async fn get_blueprint_materials(
     .await
 }
 
+/// Get manufacturing skill requirements of several blueprints
+pub async fn get_blueprint_skills(
+    pool: &DbPool,
+    blueprint_type_ids: &[i32],
+) -> sqlx::Result<Vec<BlueprintSkill>> {
+    if blueprint_type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = blueprint_type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT * FROM blueprint_skills WHERE blueprint_type_id IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, BlueprintSkill>(&query);
+    for blueprint_type_id in blueprint_type_ids {
+        query = query.bind(blueprint_type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
+/// Get the specific processing skill of several reprocessable types
+pub async fn get_reprocessing_skills(
+    pool: &DbPool,
+    source_type_ids: &[i32],
+) -> sqlx::Result<Vec<ReprocessingSkill>> {
+    if source_type_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = source_type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT * FROM reprocessing_skills WHERE source_type_id IN ({})",
+        placeholders
+    );
+
+    let mut query = sqlx::query_as::<_, ReprocessingSkill>(&query);
+    for type_id in source_type_ids {
+        query = query.bind(type_id);
+    }
+
+    query.fetch_all(pool).await
+}
+
 /// Get all blueprint materials for multiple blueprints
 pub async fn get_all_blueprint_materials(pool: &DbPool) -> sqlx::Result<Vec<BlueprintMaterial>> {
     sqlx::query_as::<_, BlueprintMaterial>("SELECT * FROM blueprint_materials")
@@ -75,26 +471,41 @@ pub async fn get_all_blueprint_materials(pool: &DbPool) -> sqlx::Result<Vec<Blue
         .await
 }
 
-/// Get market price for a type
-pub async fn get_market_price(pool: &DbPool, type_id: i32) -> sqlx::Result<Option<MarketPrice>> {
+/// Get the market price for a type at a location
+pub async fn get_market_price(
+    pool: &DbPool,
+    type_id: i32,
+    location: MarketLocation,
+) -> sqlx::Result<Option<MarketPrice>> {
     sqlx::query_as::<_, MarketPrice>(
-        "SELECT * FROM market_prices WHERE type_id = ? LIMIT 1"
+        "SELECT * FROM market_prices WHERE type_id = ? AND location_type = ? AND location_id = ?"
     )
     .bind(type_id)
+    .bind(location.kind())
+    .bind(location.id())
     .fetch_optional(pool)
     .await
 }
 
-/// Get market prices for multiple types
-pub async fn get_market_prices(pool: &DbPool, type_ids: &[i32]) -> sqlx::Result<Vec<MarketPrice>> {
+/// Get market prices for multiple types at a location
+pub async fn get_market_prices(
+    pool: &DbPool,
+    type_ids: &[i32],
+    location: MarketLocation,
+) -> sqlx::Result<Vec<MarketPrice>> {
     if type_ids.is_empty() {
         return Ok(vec![]);
     }
 
     let placeholders = type_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-    let query = format!("SELECT * FROM market_prices WHERE type_id IN ({})", placeholders);
+    let query = format!(
+        "SELECT * FROM market_prices WHERE location_type = ? AND location_id = ? AND type_id IN ({})",
+        placeholders
+    );
 
-    let mut query = sqlx::query_as::<_, MarketPrice>(&query);
+    let mut query = sqlx::query_as::<_, MarketPrice>(&query)
+        .bind(location.kind())
+        .bind(location.id());
     for type_id in type_ids {
         query = query.bind(type_id);
     }
@@ -102,22 +513,217 @@ pub async fn get_market_prices(pool: &DbPool, type_ids: &[i32]) -> sqlx::Result<
     query.fetch_all(pool).await
 }
 
-/// Upsert market price
-pub async fn upsert_market_price(pool: &DbPool, price: &MarketPrice) -> sqlx::Result<()> {
+/// Upsert market prices (one row per type and location) and append them to the price history,
+/// all in one transaction
+pub async fn upsert_market_prices(pool: &DbPool, prices: &[MarketPrice]) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for price in prices {
+        sqlx::query(
+            "INSERT INTO market_prices
+             (type_id, location_type, location_id, sell_price, buy_price, daily_volume, best_bid, best_ask, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+             ON CONFLICT(type_id, location_type, location_id) DO UPDATE SET
+                sell_price = excluded.sell_price,
+                buy_price = excluded.buy_price,
+                daily_volume = excluded.daily_volume,
+                best_bid = excluded.best_bid,
+                best_ask = excluded.best_ask,
+                updated_at = CURRENT_TIMESTAMP"
+        )
+        .bind(price.type_id)
+        .bind(&price.location_type)
+        .bind(price.location_id)
+        .bind(price.sell_price)
+        .bind(price.buy_price)
+        .bind(price.daily_volume)
+        .bind(price.best_bid)
+        .bind(price.best_ask)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO market_price_history
+             (type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask, daily_volume)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(price.type_id)
+        .bind(&price.location_type)
+        .bind(price.location_id)
+        .bind(price.sell_price)
+        .bind(price.buy_price)
+        .bind(price.best_bid)
+        .bind(price.best_ask)
+        .bind(price.daily_volume)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Get a type's price refreshes at a location over the last `days` days, oldest first
+pub async fn get_price_history(
+    pool: &DbPool,
+    type_id: i32,
+    location: MarketLocation,
+    days: i64,
+) -> sqlx::Result<Vec<MarketPriceHistory>> {
+    sqlx::query_as::<_, MarketPriceHistory>(
+        "SELECT type_id, location_type, location_id, sell_price, buy_price, best_bid, best_ask,
+                daily_volume, recorded_at
+         FROM market_price_history
+         WHERE type_id = ? AND location_type = ? AND location_id = ?
+           AND recorded_at >= datetime('now', ?)
+         ORDER BY recorded_at, id"
+    )
+    .bind(type_id)
+    .bind(location.kind())
+    .bind(location.id())
+    .bind(format!("-{} days", days))
+    .fetch_all(pool)
+    .await
+}
+
+/// Get a type's average price per day at a location over the last `days` days, oldest first
+pub async fn get_daily_prices(
+    pool: &DbPool,
+    type_id: i32,
+    location: MarketLocation,
+    days: i64,
+) -> sqlx::Result<Vec<DailyPrice>> {
+    sqlx::query_as::<_, DailyPrice>(
+        "SELECT date(recorded_at) AS day, AVG(sell_price) AS sell_price, AVG(buy_price) AS buy_price,
+                COUNT(*) AS samples
+         FROM market_price_history
+         WHERE type_id = ? AND location_type = ? AND location_id = ?
+           AND recorded_at >= datetime('now', ?)
+         GROUP BY date(recorded_at)
+         ORDER BY day"
+    )
+    .bind(type_id)
+    .bind(location.kind())
+    .bind(location.id())
+    .bind(format!("-{} days", days))
+    .fetch_all(pool)
+    .await
+}
+
+/// Every (location_type, location_id, type_id) in the market price cache
+pub async fn get_cached_price_keys(pool: &DbPool) -> sqlx::Result<Vec<(String, i64, i32)>> {
+    sqlx::query_as(
+        "SELECT location_type, location_id, type_id FROM market_prices
+         ORDER BY location_type, location_id, type_id"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record the start of a price refresh run, returning its run_id
+pub async fn start_price_refresh_run(pool: &DbPool) -> sqlx::Result<i64> {
+    let result = sqlx::query("INSERT INTO price_refresh_runs (status) VALUES ('running')")
+        .execute(pool)
+        .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Record how a price refresh run ended
+pub async fn finish_price_refresh_run(pool: &DbPool, run: &PriceRefreshRun) -> sqlx::Result<()> {
+    sqlx::query(
+        "UPDATE price_refresh_runs SET
+            finished_at = CURRENT_TIMESTAMP,
+            status = ?,
+            locations = ?,
+            updated_count = ?,
+            expires_at = ?,
+            next_run_at = ?,
+            error = ?
+         WHERE run_id = ?"
+    )
+    .bind(&run.status)
+    .bind(run.locations)
+    .bind(run.updated_count)
+    .bind(&run.expires_at)
+    .bind(&run.next_run_at)
+    .bind(&run.error)
+    .bind(run.run_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark runs left 'running' by a server that stopped mid-refresh as failed
+pub async fn fail_unfinished_price_refresh_runs(pool: &DbPool) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE price_refresh_runs
+         SET status = 'failed', finished_at = CURRENT_TIMESTAMP, error = 'interrupted'
+         WHERE status = 'running'"
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Latest price refresh run, optionally only one with the given status
+pub async fn get_last_price_refresh_run(
+    pool: &DbPool,
+    status: Option<&str>,
+) -> sqlx::Result<Option<PriceRefreshRun>> {
+    sqlx::query_as::<_, PriceRefreshRun>(
+        "SELECT * FROM price_refresh_runs
+         WHERE (?1 IS NULL OR status = ?1)
+         ORDER BY run_id DESC LIMIT 1"
+    )
+    .bind(status)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Get the cached response for a URL
+pub async fn get_http_cache_entry(pool: &DbPool, url: &str) -> sqlx::Result<Option<HttpCacheEntry>> {
+    sqlx::query_as::<_, HttpCacheEntry>("SELECT * FROM http_cache WHERE url = ?")
+        .bind(url)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Store a fresh response for a URL
+pub async fn upsert_http_cache_entry(pool: &DbPool, entry: &HttpCacheEntry) -> sqlx::Result<()> {
     sqlx::query(
-        "INSERT INTO market_prices (type_id, region_id, sell_price, buy_price, daily_volume, updated_at)
+        "INSERT INTO http_cache (url, etag, expires_at, headers, body, fetched_at)
          VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
-         ON CONFLICT(type_id) DO UPDATE SET
-            sell_price = excluded.sell_price,
-            buy_price = excluded.buy_price,
-            daily_volume = excluded.daily_volume,
-            updated_at = CURRENT_TIMESTAMP"
-    )
-    .bind(price.type_id)
-    .bind(price.region_id)
-    .bind(price.sell_price)
-    .bind(price.buy_price)
-    .bind(price.daily_volume)
+         ON CONFLICT(url) DO UPDATE SET
+            etag = excluded.etag,
+            expires_at = excluded.expires_at,
+            headers = excluded.headers,
+            body = excluded.body,
+            fetched_at = CURRENT_TIMESTAMP"
+    )
+    .bind(&entry.url)
+    .bind(&entry.etag)
+    .bind(&entry.expires_at)
+    .bind(&entry.headers)
+    .bind(&entry.body)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record that a cached response was revalidated (304 Not Modified)
+pub async fn touch_http_cache_entry(
+    pool: &DbPool,
+    url: &str,
+    expires_at: Option<&str>,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        "UPDATE http_cache SET expires_at = ?, fetched_at = CURRENT_TIMESTAMP WHERE url = ?"
+    )
+    .bind(expires_at)
+    .bind(url)
     .execute(pool)
     .await?;
 
@@ -5,6 +5,8 @@ use axum::{
 };
 use serde_json::json;
 
+use crate::external::executor::ExternalError;
+
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
     #[error("Database error: {0}")]
@@ -13,6 +15,9 @@ pub enum ApiError {
     #[error("External API error: {0}")]
     ExternalApi(String),
 
+    #[error("External API error: {0}")]
+    External(#[from] ExternalError),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
@@ -38,6 +43,7 @@ impl IntoResponse for ApiError {
                 tracing::error!("Not found: {}", msg);
                 (StatusCode::NOT_FOUND, msg.as_str())
             }
+            ApiError::External(ref e) => return external_error_response(e),
             ApiError::InvalidInput(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
             ApiError::Internal(ref msg) => {
                 tracing::error!("Internal error: {}", msg);
@@ -53,4 +59,44 @@ impl IntoResponse for ApiError {
     }
 }
 
+/// External API failures, with what went wrong upstream in `upstream`
+fn external_error_response(error: &ExternalError) -> Response {
+    tracing::error!("External API error: {}", error);
+
+    let (status, upstream) = match error {
+        ExternalError::Network { url, attempts, .. } => (
+            StatusCode::BAD_GATEWAY,
+            json!({"kind": "network", "url": url, "attempts": attempts}),
+        ),
+        ExternalError::Status { url, status, attempts, .. } => (
+            StatusCode::BAD_GATEWAY,
+            json!({"kind": "status", "url": url, "status": status, "attempts": attempts}),
+        ),
+        ExternalError::ErrorLimited { retry_after_secs } => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            json!({"kind": "error_limited", "retry_after_secs": retry_after_secs}),
+        ),
+        ExternalError::RateLimited { url, retry_after_secs } => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            json!({"kind": "rate_limited", "url": url, "retry_after_secs": retry_after_secs}),
+        ),
+        ExternalError::InvalidResponse { url, .. } => (
+            StatusCode::BAD_GATEWAY,
+            json!({"kind": "invalid_response", "url": url}),
+        ),
+        ExternalError::Cache(_) => (StatusCode::INTERNAL_SERVER_ERROR, json!({"kind": "cache"})),
+    };
+
+    let body = Json(json!({
+        "error": error.to_string(),
+        "upstream": upstream,
+    }));
+
+    let mut response = (status, body).into_response();
+    if let Some(seconds) = error.retry_after_secs() {
+        response.headers_mut().insert(axum::http::header::RETRY_AFTER, seconds.into());
+    }
+    response
+}
+
 pub type ApiResult<T> = Result<T, ApiError>;
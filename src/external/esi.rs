@@ -1,44 +1,199 @@
-use reqwest::Client;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use super::executor::{ExternalError, RequestExecutor};
+use super::http_cache::{self, HttpCache};
 
 const ESI_BASE_URL: &str = "https://esi.evetech.net/latest";
 
+/// Order book pages requested at once
+const CONCURRENT_PAGES: usize = 8;
+
 pub struct EsiClient {
-    client: Client,
+    executor: Arc<RequestExecutor>,
+    base_url: String,
+    cache: Option<HttpCache>,
 }
 
 impl EsiClient {
+    /// Client for `ESI_BASE_URL`, or esi.evetech.net if unset
     pub fn new() -> Self {
+        let base_url = std::env::var("ESI_BASE_URL").unwrap_or_else(|_| ESI_BASE_URL.to_string());
+        Self::with_base_url(&base_url)
+    }
+
+    /// Client for another base URL (e.g. a local mock server)
+    pub fn with_base_url(base_url: &str) -> Self {
         Self {
-            client: Client::new(),
+            executor: RequestExecutor::shared(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cache: None,
         }
     }
 
-    /// Fetch market prices from ESI
-    pub async fn get_market_prices(&self, region_id: i32, type_id: i32) -> anyhow::Result<Vec<MarketOrder>> {
-        let url = format!(
-            "{}/markets/{}/orders/?type_id={}",
-            ESI_BASE_URL, region_id, type_id
-        );
+    /// Cache responses, sending conditional requests once they expire
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Fetch every buy and sell order in a region, following `X-Pages`
+    ///
+    /// With `type_id`, only that type's orders are fetched.
+    pub async fn get_region_orders(
+        &self,
+        region_id: i32,
+        type_id: Option<i32>,
+    ) -> Result<RegionOrders, ExternalError> {
+        let mut url = format!("{}/markets/{}/orders/?order_type=all", self.base_url, region_id);
+        if let Some(type_id) = type_id {
+            url.push_str(&format!("&type_id={}", type_id));
+        }
+
+        let first = fetch_order_page(&self.executor, self.cache.as_ref(), &url, 1).await?;
+        let pages = first.pages;
+        let mut expires = first.expires;
+        let mut orders = first.orders;
+
+        let mut remaining = (2..=pages).peekable();
+        while remaining.peek().is_some() {
+            let mut requests = JoinSet::new();
+            for page in remaining.by_ref().take(CONCURRENT_PAGES) {
+                let executor = self.executor.clone();
+                let cache = self.cache.clone();
+                let url = url.clone();
+                requests.spawn(async move { fetch_order_page(&executor, cache.as_ref(), &url, page).await });
+            }
 
-        let response = self.client.get(&url).send().await?;
-        let orders: Vec<MarketOrder> = response.json().await?;
+            while let Some(result) = requests.join_next().await {
+                let page = result.map_err(|e| ExternalError::InvalidResponse {
+                    url: url.clone(),
+                    message: e.to_string(),
+                })??;
+                expires = expires.max(page.expires);
+                orders.extend(page.orders);
+            }
+        }
+
+        Ok(RegionOrders { orders, expires })
+    }
+
+    /// Fetch global average prices for every type from ESI `/markets/prices/`
+    pub async fn get_average_prices(&self) -> Result<HashMap<i32, EsiPrice>, ExternalError> {
+        let url = format!("{}/markets/prices/", self.base_url);
+
+        let response = http_cache::get(&self.executor, self.cache.as_ref(), &url).await?;
+        let prices: Vec<EsiPrice> = response.json()?;
 
-        Ok(orders)
+        Ok(prices.into_iter().map(|p| (p.type_id, p)).collect())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// One page of a region's orders
+struct OrderPage {
+    orders: Vec<MarketOrder>,
+    /// Total page count, from `X-Pages`
+    pages: u32,
+    expires: Option<DateTime<Utc>>,
+}
+
+/// Fetch one page of orders
+async fn fetch_order_page(
+    executor: &RequestExecutor,
+    cache: Option<&HttpCache>,
+    url: &str,
+    page: u32,
+) -> Result<OrderPage, ExternalError> {
+    let response = http_cache::get(executor, cache, &format!("{}&page={}", url, page)).await?;
+
+    let pages = response
+        .header("x-pages")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(1);
+    let orders: Vec<MarketOrder> = response.json()?;
+
+    Ok(OrderPage { orders, pages, expires: response.expires })
+}
+
+/// A region's orders, with the time ESI will next refresh them
+#[derive(Debug, Clone)]
+pub struct RegionOrders {
+    pub orders: Vec<MarketOrder>,
+    /// Latest `Expires` of the fetched pages; refetching earlier returns the same data
+    pub expires: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketOrder {
     pub order_id: i64,
     pub type_id: i32,
+    /// Station or structure the order is placed at
+    pub location_id: i64,
     pub price: f64,
     pub volume_remain: i32,
     pub is_buy_order: bool,
 }
 
+/// Global average price of a type, from ESI `/markets/prices/`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EsiPrice {
+    pub type_id: i32,
+    pub average_price: Option<f64>,
+    pub adjusted_price: Option<f64>,
+}
+
 impl Default for EsiClient {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{
+        extract::Query,
+        response::IntoResponse,
+        routing::get,
+        Json, Router,
+    };
+
+    #[derive(Deserialize)]
+    struct PageQuery {
+        page: u32,
+    }
+
+    /// Serve a three-page order book, returning its base URL
+    async fn mock_server() -> String {
+        let app = Router::new().route(
+            "/markets/10000002/orders/",
+            get(|Query(query): Query<PageQuery>| async move {
+                let order = serde_json::json!({
+                    "order_id": query.page, "type_id": 34, "location_id": 60003760,
+                    "price": 5.0, "volume_remain": 100, "is_buy_order": false,
+                    "duration": 90, "issued": "2024-11-01T00:00:00Z"
+                });
+                let headers = [("x-pages", "3"), ("expires", "Mon, 11 Nov 2024 12:05:00 GMT")];
+                (headers, Json(vec![order])).into_response()
+            }),
+        );
+
+        test_support::serve(app).await
+    }
+
+    #[tokio::test]
+    async fn test_get_region_orders_follows_pages() {
+        let client = EsiClient::with_base_url(&mock_server().await);
+
+        let book = client.get_region_orders(10000002, None).await.unwrap();
+
+        let mut order_ids: Vec<i64> = book.orders.iter().map(|o| o.order_id).collect();
+        order_ids.sort_unstable();
+        assert_eq!(order_ids, vec![1, 2, 3]);
+        assert_eq!(book.expires.unwrap().to_rfc3339(), "2024-11-11T12:05:00+00:00");
+    }
+}
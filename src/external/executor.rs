@@ -0,0 +1,380 @@
+use reqwest::{header::HeaderMap, Client, RequestBuilder, Response, StatusCode};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+
+/// Requests in flight at once across every external client
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// With this few errors left in ESI's window, requests wait for the window to reset
+const ERROR_LIMIT_FLOOR: u32 = 10;
+
+/// Longest an upstream error message is kept
+const MAX_ERROR_MESSAGE_LEN: usize = 200;
+
+/// A failed external API request
+#[derive(Debug, thiserror::Error)]
+pub enum ExternalError {
+    /// Connection failure or timeout, after retrying
+    #[error("request to {url} failed after {attempts} attempts: {message}")]
+    Network { url: String, attempts: u32, message: String },
+
+    /// Error status from the API (transient ones only after retrying)
+    #[error("{url} returned {status} after {attempts} attempts: {message}")]
+    Status { url: String, status: u16, attempts: u32, message: String },
+
+    /// ESI stopped answering because too many requests failed (HTTP 420)
+    #[error("ESI error limit reached; retry in {retry_after_secs}s")]
+    ErrorLimited { retry_after_secs: u64 },
+
+    /// The API asked to wait longer than the retry policy allows (`Retry-After`)
+    #[error("{url} is rate limited; retry in {retry_after_secs}s")]
+    RateLimited { url: String, retry_after_secs: u64 },
+
+    /// The response body could not be read or parsed
+    #[error("invalid response from {url}: {message}")]
+    InvalidResponse { url: String, message: String },
+
+    /// The response cache could not be read or written
+    #[error("HTTP cache error: {0}")]
+    Cache(#[from] sqlx::Error),
+}
+
+impl ExternalError {
+    /// Seconds until the request is worth retrying, when the API said so
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            ExternalError::ErrorLimited { retry_after_secs }
+            | ExternalError::RateLimited { retry_after_secs, .. } => Some(*retry_after_secs),
+            _ => None,
+        }
+    }
+}
+
+/// How failed requests are retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each further one
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+/// ESI's error budget, from the `X-ESI-Error-Limit-*` headers of the latest response
+#[derive(Debug, Default)]
+struct ErrorLimit {
+    remain: Option<u32>,
+    reset_at: Option<Instant>,
+}
+
+/// Sends requests for the external clients: limits concurrency, stays within ESI's
+/// error limit and retries transient failures with jittered exponential backoff
+pub struct RequestExecutor {
+    client: Client,
+    permits: Semaphore,
+    error_limit: Mutex<ErrorLimit>,
+    policy: RetryPolicy,
+}
+
+impl RequestExecutor {
+    pub fn new(policy: RetryPolicy, max_concurrent: usize) -> Self {
+        let user_agent = std::env::var("ESI_USER_AGENT")
+            .unwrap_or_else(|_| "Salvo-Industrial-Planner/1.0".to_string());
+
+        Self {
+            client: Client::builder()
+                .user_agent(user_agent)
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap(),
+            permits: Semaphore::new(max_concurrent),
+            error_limit: Mutex::new(ErrorLimit::default()),
+            policy,
+        }
+    }
+
+    /// The executor shared by every external client, so limits apply process-wide
+    pub fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<RequestExecutor>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(Self::new(RetryPolicy::default(), MAX_CONCURRENT_REQUESTS)))
+            .clone()
+    }
+
+    /// Send the request built by `build`, retrying transient failures
+    ///
+    /// Successful and `304 Not Modified` responses are returned; other statuses become errors.
+    pub async fn send<F>(&self, url: &str, build: F) -> Result<Response, ExternalError>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            self.wait_for_error_budget().await;
+
+            let result = {
+                let _permit = self.permits.acquire().await.expect("semaphore is never closed");
+                build(&self.client).send().await
+            };
+
+            let retry_after = match result {
+                Ok(response) => {
+                    self.record_error_limit(response.headers());
+
+                    let status = response.status();
+                    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                        return Ok(response);
+                    }
+
+                    // Retrying into ESI's error limit only extends the ban
+                    if status.as_u16() == 420 {
+                        return Err(ExternalError::ErrorLimited {
+                            retry_after_secs: self.error_limit_reset().as_secs(),
+                        });
+                    }
+
+                    let retry_after = retry_after(response.headers());
+                    // Waiting out a long Retry-After would hold the caller's request open
+                    if let Some(after) = retry_after.filter(|after| is_transient(status) && *after > self.policy.max_delay) {
+                        return Err(ExternalError::RateLimited {
+                            url: url.to_string(),
+                            retry_after_secs: after.as_secs(),
+                        });
+                    }
+                    if !is_transient(status) || attempt >= self.policy.max_attempts {
+                        let message = response.text().await.unwrap_or_default();
+                        return Err(ExternalError::Status {
+                            url: url.to_string(),
+                            status: status.as_u16(),
+                            attempts: attempt,
+                            message: truncate(message),
+                        });
+                    }
+
+                    tracing::warn!("{} returned {} (attempt {}), retrying", url, status, attempt);
+                    retry_after
+                }
+                Err(e) => {
+                    if attempt >= self.policy.max_attempts || !(e.is_timeout() || e.is_connect()) {
+                        return Err(ExternalError::Network {
+                            url: url.to_string(),
+                            attempts: attempt,
+                            message: e.to_string(),
+                        });
+                    }
+
+                    tracing::warn!("Request to {} failed (attempt {}): {}, retrying", url, attempt, e);
+                    None
+                }
+            };
+
+            // Both are within max_delay: longer Retry-After waits were returned above
+            let delay = backoff(&self.policy, attempt, jitter());
+            tokio::time::sleep(retry_after.map_or(delay, |after| after.max(delay))).await;
+        }
+    }
+
+    /// Pause while ESI's error budget is nearly spent, until its window resets
+    async fn wait_for_error_budget(&self) {
+        let wait = {
+            let limit = self.error_limit.lock().unwrap();
+            match (limit.remain, limit.reset_at) {
+                (Some(remain), Some(reset_at)) if remain <= ERROR_LIMIT_FLOOR => {
+                    reset_at.checked_duration_since(Instant::now())
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(wait) = wait {
+            tracing::warn!("ESI error limit nearly reached, pausing requests for {}s", wait.as_secs());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn record_error_limit(&self, headers: &HeaderMap) {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.parse::<u64>().ok());
+        let (Some(remain), Some(reset)) = (header("x-esi-error-limit-remain"), header("x-esi-error-limit-reset")) else {
+            return;
+        };
+
+        let mut limit = self.error_limit.lock().unwrap();
+        limit.remain = Some(remain as u32);
+        limit.reset_at = Some(Instant::now() + Duration::from_secs(reset));
+    }
+
+    /// Time until ESI's error window resets (zero if unknown)
+    fn error_limit_reset(&self) -> Duration {
+        let limit = self.error_limit.lock().unwrap();
+        limit
+            .reset_at
+            .and_then(|reset_at| reset_at.checked_duration_since(Instant::now()))
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Statuses worth retrying: rate limits and upstream outages
+fn is_transient(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// Delay before retry number `attempt`: exponential, capped, scaled by `jitter` (0.0 to 1.0)
+/// into the upper half of the range so concurrent retries spread out
+fn backoff(policy: &RetryPolicy, attempt: u32, jitter: f64) -> Duration {
+    let exponential = policy.base_delay.saturating_mul(1 << (attempt - 1).min(16));
+    let delay = exponential.min(policy.max_delay);
+    delay.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// Pseudo-random number in [0, 1) from the clock, enough to spread out retries
+fn jitter() -> f64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    (nanos % 1000) as f64 / 1000.0
+}
+
+/// Seconds to wait from a `Retry-After` header
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+fn truncate(mut message: String) -> String {
+    if message.len() > MAX_ERROR_MESSAGE_LEN {
+        let mut end = MAX_ERROR_MESSAGE_LEN;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{http::StatusCode as AxumStatus, response::IntoResponse, routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    /// Serve 502 `failures` times, then 200; returns the URL and the request count
+    async fn flaky_server(failures: usize) -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+
+        let app = Router::new().route(
+            "/status/",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    let headers = [("x-esi-error-limit-remain", "90"), ("x-esi-error-limit-reset", "30")];
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        (AxumStatus::BAD_GATEWAY, headers, "upstream down").into_response()
+                    } else {
+                        (headers, "ok").into_response()
+                    }
+                }
+            }),
+        );
+
+        (format!("{}/status/", test_support::serve(app).await), requests)
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let (url, requests) = flaky_server(2).await;
+        let executor = RequestExecutor::new(fast_policy(), 4);
+
+        let response = executor.send(&url, |client| client.get(&url)).await.unwrap();
+
+        assert_eq!(response.text().await.unwrap(), "ok");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(executor.error_limit.lock().unwrap().remain, Some(90));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_with_structured_error() {
+        let (url, requests) = flaky_server(usize::MAX).await;
+        let executor = RequestExecutor::new(fast_policy(), 4);
+
+        let error = executor.send(&url, |client| client.get(&url)).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            ExternalError::Status { status: 502, attempts: 3, ref message, .. } if message == "upstream down"
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_error_limited_is_not_retried() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/status/",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { (AxumStatus::from_u16(420).unwrap(), "error limited").into_response() }
+            }),
+        );
+        let url = format!("{}/status/", test_support::serve(app).await);
+        let executor = RequestExecutor::new(fast_policy(), 4);
+
+        let error = executor.send(&url, |client| client.get(&url)).await.unwrap_err();
+
+        assert!(matches!(error, ExternalError::ErrorLimited { .. }));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_is_returned_instead_of_waited_out() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/status/",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { (AxumStatus::TOO_MANY_REQUESTS, [("retry-after", "120")], "slow down").into_response() }
+            }),
+        );
+        let url = format!("{}/status/", test_support::serve(app).await);
+        let executor = RequestExecutor::new(fast_policy(), 4);
+
+        let error = executor.send(&url, |client| client.get(&url)).await.unwrap_err();
+
+        assert!(matches!(error, ExternalError::RateLimited { retry_after_secs: 120, .. }));
+        assert_eq!(error.retry_after_secs(), Some(120));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles_within_bounds() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(backoff(&policy, 1, 1.0), Duration::from_millis(500));
+        assert_eq!(backoff(&policy, 3, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff(&policy, 3, 0.0), Duration::from_secs(1));
+        // Capped at max_delay
+        assert_eq!(backoff(&policy, 10, 1.0), Duration::from_secs(8));
+    }
+}
@@ -1,73 +1,105 @@
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-const ESI_BASE_URL: &str = "https://esi.evetech.net/latest";
+use super::executor::{ExternalError, RequestExecutor};
+use super::http_cache::{self, HttpCache};
 
-pub struct EsiClient {
-    client: Client,
+use crate::db::models::MarketLocation;
+
+const FUZZWORKS_BASE_URL: &str = "https://market.fuzzwork.co.uk";
+
+/// Types per aggregates request (keeps the query string a sensible length)
+const TYPES_PER_REQUEST: usize = 200;
+
+/// Query parameter for the aggregates endpoint; Fuzzworks has no global market
+fn location_query(location: MarketLocation) -> Option<String> {
+    match location {
+        MarketLocation::Region(region_id) => Some(format!("region={}", region_id)),
+        MarketLocation::Station(station_id) => Some(format!("station={}", station_id)),
+        MarketLocation::Global => None,
+    }
 }
 
-impl EsiClient {
+/// Client for the Fuzzworks market aggregates API
+pub struct FuzzworksClient {
+    executor: Arc<RequestExecutor>,
+    base_url: String,
+    cache: Option<HttpCache>,
+}
+
+impl FuzzworksClient {
+    /// Client for `FUZZWORKS_BASE_URL`, or market.fuzzwork.co.uk if unset
     pub fn new() -> Self {
+        let base_url = std::env::var("FUZZWORKS_BASE_URL")
+            .unwrap_or_else(|_| FUZZWORKS_BASE_URL.to_string());
+        Self::with_base_url(&base_url)
+    }
+
+    /// Client for another base URL (e.g. a local mock server)
+    pub fn with_base_url(base_url: &str) -> Self {
         Self {
-            client: Client::builder()
-                .user_agent("Salvo-Industrial-Planner/1.0")
-                .build()
-                .unwrap(),
+            executor: RequestExecutor::shared(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            cache: None,
         }
     }
 
-    /// Fetch market prices from ESI
-    /// Returns global average prices
-    pub async fn get_market_data(&self, _type_ids: &[i32]) -> anyhow::Result<HashMap<i32, MarketAggregate>> {
-        let url = format!("{}/markets/prices/", ESI_BASE_URL);
-
-        let response = self.client.get(&url).send().await?;
-        let prices: Vec<EsiPrice> = response.json().await?;
+    /// Cache responses, sending conditional requests once they expire
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-        // Convert to our internal format
+    /// Fetch buy and sell aggregates for types at a region or station
+    ///
+    /// Types without any orders at the location are missing from the result; at
+    /// `MarketLocation::Global` the result is empty.
+    pub async fn get_aggregates(
+        &self,
+        location: MarketLocation,
+        type_ids: &[i32],
+    ) -> Result<Aggregates, ExternalError> {
+        let Some(query) = location_query(location) else {
+            return Ok(Aggregates::default());
+        };
         let mut result = HashMap::new();
-        for price in prices {
-            result.insert(
-                price.type_id,
-                MarketAggregate {
-                    buy: PriceData {
-                        weighted_average: price.average_price.unwrap_or(0.0),
-                        max: 0.0,
-                        min: 0.0,
-                        stddev: 0.0,
-                        median: 0.0,
-                        volume: 0,
-                        order_count: 0,
-                        percentile: 0.0,
-                    },
-                    sell: PriceData {
-                        weighted_average: price.average_price.unwrap_or(0.0),
-                        max: 0.0,
-                        min: 0.0,
-                        stddev: 0.0,
-                        median: 0.0,
-                        volume: 0,
-                        order_count: 0,
-                        percentile: 0.0,
-                    },
-                },
-            );
+        let mut expires: Option<DateTime<Utc>> = None;
+
+        for chunk in type_ids.chunks(TYPES_PER_REQUEST) {
+            let types = chunk.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+            let url = format!("{}/aggregates/?{}&types={}", self.base_url, query, types);
+
+            let response = http_cache::get(&self.executor, self.cache.as_ref(), &url).await?;
+            let aggregates: HashMap<String, MarketAggregate> = response.json()?;
+            expires = match (expires, response.expires) {
+                (Some(current), Some(next)) => Some(current.min(next)),
+                (current, next) => current.or(next),
+            };
+
+            for (type_id, aggregate) in aggregates {
+                let type_id: i32 = type_id.parse().map_err(|_| ExternalError::InvalidResponse {
+                    url: url.clone(),
+                    message: format!("type ID '{}' is not a number", type_id),
+                })?;
+                if aggregate.buy.order_count > 0 || aggregate.sell.order_count > 0 {
+                    result.insert(type_id, aggregate);
+                }
+            }
         }
 
-        Ok(result)
+        Ok(Aggregates { aggregates: result, expires })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct EsiPrice {
-    #[serde(rename = "type_id")]
-    pub type_id: i32,
-    #[serde(rename = "average_price")]
-    pub average_price: Option<f64>,
-    #[serde(rename = "adjusted_price")]
-    pub adjusted_price: Option<f64>,
+/// Aggregates of several types, with the time Fuzzworks will next refresh them
+#[derive(Debug, Default)]
+pub struct Aggregates {
+    /// Types without any orders at the location are left out
+    pub aggregates: HashMap<i32, MarketAggregate>,
+    /// Earliest `Expires` of the fetched chunks; refetching earlier returns the same data
+    pub expires: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -76,38 +108,130 @@ pub struct MarketAggregate {
     pub sell: PriceData,
 }
 
+/// Aggregated orders on one side of the market
+///
+/// Fuzzworks sends every value as a string (e.g. `"5.95"`).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PriceData {
-    #[serde(rename = "weightedAverage")]
+    #[serde(rename = "weightedAverage", deserialize_with = "number")]
     pub weighted_average: f64,
+    #[serde(deserialize_with = "number")]
     pub max: f64,
+    #[serde(deserialize_with = "number")]
     pub min: f64,
+    #[serde(deserialize_with = "number")]
     pub stddev: f64,
+    #[serde(deserialize_with = "number")]
     pub median: f64,
+    /// Units on the market
+    #[serde(deserialize_with = "integer")]
     pub volume: i64,
-    #[serde(rename = "orderCount")]
+    #[serde(rename = "orderCount", deserialize_with = "integer")]
     pub order_count: i64,
+    /// 5th percentile price: lowest sells, or highest buys, weighted by volume
+    #[serde(deserialize_with = "number")]
     pub percentile: f64,
 }
 
-// Re-export with old field names for backwards compatibility
-impl PriceData {
-    #[allow(non_snake_case)]
-    pub fn weightedAverage(&self) -> f64 {
-        self.weighted_average
+/// Accept a number either as JSON number or as a numeric string
+fn number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Float(f64),
+        Text(String),
     }
 
-    #[allow(non_snake_case)]
-    pub fn orderCount(&self) -> i64 {
-        self.order_count
+    match Number::deserialize(deserializer)? {
+        Number::Float(value) => Ok(value),
+        Number::Text(text) => text.parse().map_err(serde::de::Error::custom),
     }
 }
 
-impl Default for EsiClient {
+/// Accept a whole number either as JSON number or as a numeric string (e.g. `"52"`)
+fn integer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    number(deserializer).map(|value| value as i64)
+}
+
+impl Default for FuzzworksClient {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// Backwards compatibility alias
-pub type FuzzworksClient = EsiClient;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{extract::RawQuery, routing::get, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serve canned aggregates, returning the base URL and the request count
+    async fn mock_server() -> (String, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let app = Router::new().route(
+            "/aggregates/",
+            get(move |RawQuery(query): RawQuery| async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(query.as_deref(), Some("station=60003760&types=34,35"));
+                // Far enough ahead that the cached response is still fresh when reused
+                let expires = (Utc::now() + chrono::Duration::minutes(5)).to_rfc2822();
+                ([("expires", expires)], Json(serde_json::json!({
+                    "34": {
+                        "buy": {"weightedAverage": "4.02", "max": "4.5", "min": "0.01", "stddev": "1.2",
+                                "median": "4.1", "volume": "5000000", "orderCount": "52", "percentile": "4.4"},
+                        "sell": {"weightedAverage": "5.1", "max": "9.0", "min": "4.9", "stddev": "0.8",
+                                 "median": "5.2", "volume": "8000000", "orderCount": "40", "percentile": "4.95"}
+                    },
+                    "35": {
+                        "buy": {"weightedAverage": "0", "max": "0", "min": "0", "stddev": "0",
+                                "median": "0", "volume": "0", "orderCount": "0", "percentile": "0"},
+                        "sell": {"weightedAverage": "0", "max": "0", "min": "0", "stddev": "0",
+                                 "median": "0", "volume": "0", "orderCount": "0", "percentile": "0"}
+                    }
+                })))
+            }),
+        );
+
+        (test_support::serve(app).await, requests)
+    }
+
+    #[tokio::test]
+    async fn test_get_aggregates_from_mock_server() {
+        let (url, _) = mock_server().await;
+        let client = FuzzworksClient::with_base_url(&url);
+
+        let result = client
+            .get_aggregates(MarketLocation::Station(60003760), &[34, 35])
+            .await
+            .unwrap();
+        let aggregates = &result.aggregates;
+
+        // Types without orders are left out
+        assert_eq!(aggregates.len(), 1);
+
+        let tritanium = &aggregates[&34];
+        assert_eq!(tritanium.buy.max, 4.5);
+        assert_eq!(tritanium.buy.percentile, 4.4);
+        assert_eq!(tritanium.sell.min, 4.9);
+        assert_eq!(tritanium.sell.weighted_average, 5.1);
+        assert_eq!(tritanium.sell.volume, 8000000);
+        assert_eq!(tritanium.sell.order_count, 40);
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_reuses_unexpired_aggregates() {
+        let (url, requests) = mock_server().await;
+        let client = FuzzworksClient::with_base_url(&url).with_cache(HttpCache::new(test_support::pool().await));
+        let location = MarketLocation::Station(60003760);
+
+        let first = client.get_aggregates(location, &[34, 35]).await.unwrap();
+        let second = client.get_aggregates(location, &[34, 35]).await.unwrap();
+
+        assert!(first.expires.is_some_and(|expires| expires > Utc::now()));
+        assert_eq!(second.expires, first.expires);
+        assert_eq!(second.aggregates.len(), 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use reqwest::{header, StatusCode};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+use super::executor::{ExternalError, RequestExecutor};
+use crate::db::{format_timestamp, models::HttpCacheEntry, parse_timestamp, queries, DbPool};
+
+/// Persistent cache of GET responses, kept in the http_cache table
+///
+/// Until a response's `Expires` time it is served without a request. After that it is
+/// revalidated with `If-None-Match`; a `304 Not Modified` reuses the stored body.
+#[derive(Clone)]
+pub struct HttpCache {
+    pool: DbPool,
+}
+
+/// A GET response, fetched or from the cache
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub url: String,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+    pub expires: Option<DateTime<Utc>>,
+    /// Whether the body came from the cache (unexpired, or revalidated with a 304)
+    pub from_cache: bool,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ExternalError> {
+        serde_json::from_slice(&self.body).map_err(|e| ExternalError::InvalidResponse {
+            url: self.url.clone(),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl HttpCache {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// GET a URL, answering from the cache when it is still valid
+    pub async fn get(&self, executor: &RequestExecutor, url: &str) -> Result<HttpResponse, ExternalError> {
+        let cached = queries::get_http_cache_entry(&self.pool, url).await?;
+
+        if let Some(entry) = &cached {
+            let expires = entry.expires_at.as_deref().and_then(parse_timestamp);
+            if expires.is_some_and(|expires| expires > Utc::now()) {
+                return cached_response(entry, expires);
+            }
+        }
+
+        let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+        let response = executor
+            .send(url, |client| {
+                let request = client.get(url);
+                match etag {
+                    Some(etag) => request.header(header::IF_NONE_MATCH, etag),
+                    None => request,
+                }
+            })
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = &cached {
+                let expires = expires(response.headers());
+                let expires_at = expires.map(format_timestamp);
+                queries::touch_http_cache_entry(&self.pool, url, expires_at.as_deref()).await?;
+                return cached_response(entry, expires);
+            }
+        }
+
+        let response = read_response(url, response).await?;
+
+        let entry = HttpCacheEntry {
+            url: url.to_string(),
+            etag: response.header("etag").map(str::to_string),
+            expires_at: response.expires.map(format_timestamp),
+            headers: serde_json::to_string(&response.headers).unwrap_or_else(|_| "{}".to_string()),
+            body: response.body.clone(),
+            fetched_at: String::new(),
+        };
+        // Responses that can be neither revalidated nor reused aren't worth storing
+        if entry.etag.is_some() || entry.expires_at.is_some() {
+            queries::upsert_http_cache_entry(&self.pool, &entry).await?;
+        }
+
+        Ok(response)
+    }
+}
+
+/// GET a URL through the cache if there is one, or directly
+pub async fn get(
+    executor: &RequestExecutor,
+    cache: Option<&HttpCache>,
+    url: &str,
+) -> Result<HttpResponse, ExternalError> {
+    match cache {
+        Some(cache) => cache.get(executor, url).await,
+        None => read_response(url, executor.send(url, |client| client.get(url)).await?).await,
+    }
+}
+
+async fn read_response(url: &str, response: reqwest::Response) -> Result<HttpResponse, ExternalError> {
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let expires = expires(response.headers());
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| ExternalError::InvalidResponse {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?
+        .to_vec();
+
+    Ok(HttpResponse {
+        url: url.to_string(),
+        headers,
+        body,
+        expires,
+        from_cache: false,
+    })
+}
+
+fn cached_response(entry: &HttpCacheEntry, expires: Option<DateTime<Utc>>) -> Result<HttpResponse, ExternalError> {
+    Ok(HttpResponse {
+        url: entry.url.clone(),
+        headers: serde_json::from_str(&entry.headers).unwrap_or_default(),
+        body: entry.body.clone(),
+        expires,
+        from_cache: true,
+    })
+}
+
+/// When a response goes stale, from its `Expires` header
+pub fn expires(headers: &header::HeaderMap) -> Option<DateTime<Utc>> {
+    let value = headers.get(header::EXPIRES)?.to_str().ok()?;
+    DateTime::parse_from_rfc2822(value).ok().map(|expires| expires.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{http::HeaderMap, response::IntoResponse, routing::get, Router};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Serve a body with an ETag (and an Expires in the past), answering 304 to matching
+    /// If-None-Match requests; returns the base URL and the number of full responses sent
+    async fn mock_server() -> (String, Arc<AtomicUsize>) {
+        let full_responses = Arc::new(AtomicUsize::new(0));
+        let counter = full_responses.clone();
+
+        let app = Router::new().route(
+            "/markets/prices/",
+            get(move |headers: HeaderMap| {
+                let counter = counter.clone();
+                async move {
+                    let response_headers = [("etag", "\"v1\""), ("expires", "Mon, 11 Nov 2024 12:05:00 GMT")];
+                    if headers.get("if-none-match").is_some_and(|etag| etag == "\"v1\"") {
+                        return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+                    }
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    (response_headers, r#"[{"type_id": 34, "average_price": 5.0}]"#).into_response()
+                }
+            }),
+        );
+
+        (test_support::serve(app).await, full_responses)
+    }
+
+    #[tokio::test]
+    async fn test_revalidates_with_etag() {
+        let pool = test_support::pool().await;
+
+        let (base_url, full_responses) = mock_server().await;
+        let url = format!("{}/markets/prices/", base_url);
+        let cache = HttpCache::new(pool);
+        let executor = RequestExecutor::shared();
+
+        let first = cache.get(&executor, &url).await.unwrap();
+        assert!(!first.from_cache);
+
+        let second = cache.get(&executor, &url).await.unwrap();
+        assert!(second.from_cache);
+        assert_eq!(second.body, first.body);
+        assert_eq!(second.header("etag"), Some("\"v1\""));
+        assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,2 +1,5 @@
 pub mod esi;
+pub mod executor;
 pub mod fuzzworks;
+pub mod http_cache;
+pub mod price_source;
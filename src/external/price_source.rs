@@ -0,0 +1,490 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::esi::EsiClient;
+use super::fuzzworks::{FuzzworksClient, PriceData};
+use crate::db::{models::MarketLocation, DbPool};
+use crate::error::{ApiError, ApiResult};
+use crate::services::order_book;
+
+/// Prices of one type from a price source
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SourcePrice {
+    pub sell_price: Option<f64>,
+    pub buy_price: Option<f64>,
+    /// Highest buy order, when the source knows it
+    #[serde(default)]
+    pub best_bid: Option<f64>,
+    /// Lowest sell order, when the source knows it
+    #[serde(default)]
+    pub best_ask: Option<f64>,
+}
+
+/// Prices fetched from a source
+#[derive(Debug, Clone, Default)]
+pub struct SourcePrices {
+    /// Types the source has no price for are left out
+    pub prices: HashMap<i32, SourcePrice>,
+    /// When the source will next have newer prices, if it says so
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// Somewhere market prices come from
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Name the source is selected by (see `PriceSourceKind`)
+    fn name(&self) -> &'static str;
+
+    /// Whether prices are the same everywhere; such a source only prices `MarketLocation::Global`
+    fn is_global(&self) -> bool {
+        false
+    }
+
+    /// Prices for `type_ids` at `location`; an empty `type_ids` asks for every type the source has
+    async fn fetch_prices(&self, location: MarketLocation, type_ids: &[i32]) -> ApiResult<SourcePrices>;
+}
+
+/// Fail unless `source` prices `location`: global sources price only the global location,
+/// and every other source only regions and stations
+pub fn check_location(source: &dyn PriceSource, location: MarketLocation) -> ApiResult<()> {
+    match (source.is_global(), location) {
+        (true, MarketLocation::Global) | (false, MarketLocation::Region(_) | MarketLocation::Station(_)) => Ok(()),
+        (true, _) => Err(ApiError::InvalidInput(format!(
+            "The {} price source has the same prices everywhere; use the global location",
+            source.name()
+        ))),
+        (false, _) => Err(ApiError::InvalidInput(format!(
+            "The {} price source has no global prices; use a region or station",
+            source.name()
+        ))),
+    }
+}
+
+/// The available price sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSourceKind {
+    /// ESI global average prices, stored at the global location
+    EsiAverage,
+    /// 5% percentiles of the ESI regional order book, at a region or station
+    EsiOrders,
+    /// Fuzzworks market aggregates
+    Fuzzworks,
+    /// A static JSON or CSV file, from `PRICE_SOURCE_FILE`
+    File,
+}
+
+impl PriceSourceKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "esi_average" => Some(PriceSourceKind::EsiAverage),
+            "esi_orders" => Some(PriceSourceKind::EsiOrders),
+            "fuzzworks" => Some(PriceSourceKind::Fuzzworks),
+            "file" => Some(PriceSourceKind::File),
+            _ => None,
+        }
+    }
+}
+
+/// Builds price sources from the configuration, sharing one ESI and one Fuzzworks client
+#[derive(Clone)]
+pub struct PriceSources {
+    pool: DbPool,
+    esi: Arc<EsiClient>,
+    fuzzworks: Arc<FuzzworksClient>,
+    default_kind: PriceSourceKind,
+    file: Option<PathBuf>,
+}
+
+impl PriceSources {
+    pub fn new(
+        pool: DbPool,
+        esi: Arc<EsiClient>,
+        fuzzworks: Arc<FuzzworksClient>,
+        default_kind: PriceSourceKind,
+        file: Option<PathBuf>,
+    ) -> Self {
+        Self { pool, esi, fuzzworks, default_kind, file }
+    }
+
+    /// Default source from `PRICE_SOURCE` (Fuzzworks if unset), file from `PRICE_SOURCE_FILE`
+    ///
+    /// Fails on an unknown source, on `esi_average` (global prices can't stand in for a
+    /// location's), and on `file` without `PRICE_SOURCE_FILE`.
+    pub fn from_env(pool: DbPool, esi: Arc<EsiClient>, fuzzworks: Arc<FuzzworksClient>) -> anyhow::Result<Self> {
+        let default_kind = match std::env::var("PRICE_SOURCE") {
+            Ok(name) => PriceSourceKind::parse(&name).ok_or_else(|| {
+                anyhow::anyhow!("Unknown PRICE_SOURCE '{}' (expected esi_orders, fuzzworks or file)", name)
+            })?,
+            Err(_) => PriceSourceKind::Fuzzworks,
+        };
+        let file = std::env::var("PRICE_SOURCE_FILE").ok().map(PathBuf::from);
+
+        match default_kind {
+            PriceSourceKind::EsiAverage => {
+                anyhow::bail!("PRICE_SOURCE can't be esi_average: its global prices have no location")
+            }
+            PriceSourceKind::File if file.is_none() => {
+                anyhow::bail!("PRICE_SOURCE=file needs PRICE_SOURCE_FILE to be set")
+            }
+            _ => {}
+        }
+
+        Ok(Self::new(pool, esi, fuzzworks, default_kind, file))
+    }
+
+    /// Which source is used when a request names none
+    pub fn default_kind(&self) -> PriceSourceKind {
+        self.default_kind
+    }
+
+    /// The shared ESI client
+    pub fn esi(&self) -> &EsiClient {
+        &self.esi
+    }
+
+    pub fn get(&self, kind: PriceSourceKind) -> ApiResult<Box<dyn PriceSource>> {
+        Ok(match kind {
+            PriceSourceKind::EsiAverage => Box::new(EsiAverageSource::new(self.esi.clone())),
+            PriceSourceKind::EsiOrders => Box::new(EsiOrderBookSource::new(self.esi.clone(), self.pool.clone())),
+            PriceSourceKind::Fuzzworks => Box::new(FuzzworksSource::new(self.fuzzworks.clone())),
+            PriceSourceKind::File => {
+                let path = self.file.clone().ok_or_else(|| {
+                    ApiError::InvalidInput("The file price source needs PRICE_SOURCE_FILE to be set".to_string())
+                })?;
+                Box::new(StaticFileSource::new(path))
+            }
+        })
+    }
+
+    /// The configured default source
+    pub fn default_source(&self) -> ApiResult<Box<dyn PriceSource>> {
+        self.get(self.default_kind)
+    }
+
+    /// The source that prices `location`: ESI averages for the global location, else the default
+    pub fn for_location(&self, location: MarketLocation) -> ApiResult<Box<dyn PriceSource>> {
+        match location {
+            MarketLocation::Global => self.get(PriceSourceKind::EsiAverage),
+            _ => self.default_source(),
+        }
+    }
+}
+
+/// ESI global average prices, used as both sell and buy price
+pub struct EsiAverageSource {
+    client: Arc<EsiClient>,
+}
+
+impl EsiAverageSource {
+    pub fn new(client: Arc<EsiClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PriceSource for EsiAverageSource {
+    fn name(&self) -> &'static str {
+        "esi_average"
+    }
+
+    fn is_global(&self) -> bool {
+        true
+    }
+
+    async fn fetch_prices(&self, _location: MarketLocation, type_ids: &[i32]) -> ApiResult<SourcePrices> {
+        let mut prices = self.client.get_average_prices().await?;
+        if !type_ids.is_empty() {
+            prices.retain(|type_id, _| type_ids.contains(type_id));
+        }
+
+        let prices = prices
+            .into_iter()
+            .map(|(type_id, price)| {
+                (type_id, SourcePrice {
+                    sell_price: price.average_price,
+                    buy_price: price.average_price,
+                    best_bid: None,
+                    best_ask: None,
+                })
+            })
+            .collect();
+
+        Ok(SourcePrices { prices, expires: None })
+    }
+}
+
+/// ESI regional order books, summarized into 5% percentiles and best bid/ask
+pub struct EsiOrderBookSource {
+    client: Arc<EsiClient>,
+    pool: DbPool,
+}
+
+impl EsiOrderBookSource {
+    /// `pool` is used to find the region a station is in
+    pub fn new(client: Arc<EsiClient>, pool: DbPool) -> Self {
+        Self { client, pool }
+    }
+}
+
+#[async_trait]
+impl PriceSource for EsiOrderBookSource {
+    fn name(&self) -> &'static str {
+        "esi_orders"
+    }
+
+    async fn fetch_prices(&self, location: MarketLocation, type_ids: &[i32]) -> ApiResult<SourcePrices> {
+        let book = order_book::fetch_location_order_book(&self.pool, &self.client, location, type_ids).await?;
+
+        let prices = book
+            .summaries
+            .into_iter()
+            .map(|(type_id, summary)| {
+                (type_id, SourcePrice {
+                    sell_price: summary.sell_percentile,
+                    buy_price: summary.buy_percentile,
+                    best_bid: summary.best_bid,
+                    best_ask: summary.best_ask,
+                })
+            })
+            .collect();
+
+        Ok(SourcePrices { prices, expires: book.expires })
+    }
+}
+
+/// Fuzzworks market aggregates: 5% percentiles, with buy max and sell min as best bid/ask
+pub struct FuzzworksSource {
+    client: Arc<FuzzworksClient>,
+}
+
+impl FuzzworksSource {
+    pub fn new(client: Arc<FuzzworksClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FuzzworksSource {
+    fn name(&self) -> &'static str {
+        "fuzzworks"
+    }
+
+    async fn fetch_prices(&self, location: MarketLocation, type_ids: &[i32]) -> ApiResult<SourcePrices> {
+        if type_ids.is_empty() {
+            return Err(ApiError::InvalidInput(
+                "The fuzzworks price source needs type_ids".to_string(),
+            ));
+        }
+
+        let result = self.client.get_aggregates(location, type_ids).await?;
+        let prices = result
+            .aggregates
+            .into_iter()
+            .map(|(type_id, aggregate)| {
+                // Percentiles ignore outlier orders; sides without orders have no price
+                let price = |side: &PriceData, value: f64| (side.order_count > 0).then_some(value);
+                (type_id, SourcePrice {
+                    sell_price: price(&aggregate.sell, aggregate.sell.percentile),
+                    buy_price: price(&aggregate.buy, aggregate.buy.percentile),
+                    best_bid: price(&aggregate.buy, aggregate.buy.max),
+                    best_ask: price(&aggregate.sell, aggregate.sell.min),
+                })
+            })
+            .collect();
+
+        Ok(SourcePrices { prices, expires: result.expires })
+    }
+}
+
+/// Prices from a static file, listed per location
+///
+/// `.csv` files have a header row naming the columns `location_type` (`region` or `station`),
+/// `location_id`, `type_id`, `sell_price`, `buy_price` and, optionally, `best_bid` and
+/// `best_ask`; empty cells are missing prices. Any other file is read as a JSON array of
+/// objects with a `location` (e.g. `{"station": 60003760}`), `type_id` and the price fields.
+/// Only rows for the requested location are used. The file is re-read on every fetch.
+pub struct StaticFileSource {
+    path: PathBuf,
+}
+
+/// One row of a static price file
+#[derive(Debug, Deserialize)]
+struct FilePrice {
+    location: MarketLocation,
+    type_id: i32,
+    #[serde(flatten)]
+    price: SourcePrice,
+}
+
+impl StaticFileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn read(&self) -> Result<Vec<FilePrice>, String> {
+        let text = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+
+        if self.path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            parse_csv(&text)
+        } else {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticFileSource {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn fetch_prices(&self, location: MarketLocation, type_ids: &[i32]) -> ApiResult<SourcePrices> {
+        let rows = self
+            .read()
+            .map_err(|e| ApiError::Internal(format!("Could not read price file {}: {}", self.path.display(), e)))?;
+
+        let prices = rows
+            .into_iter()
+            .filter(|row| row.location == location)
+            .filter(|row| type_ids.is_empty() || type_ids.contains(&row.type_id))
+            .map(|row| (row.type_id, row.price))
+            .collect();
+
+        Ok(SourcePrices { prices, expires: None })
+    }
+}
+
+/// Parse a price CSV with a header row
+fn parse_csv(text: &str) -> Result<Vec<FilePrice>, String> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<&str> = lines.next().ok_or("empty file")?.split(',').map(str::trim).collect();
+
+    let column = |name: &str| header.iter().position(|h| *h == name);
+    let location_type_column = column("location_type").ok_or("missing location_type column")?;
+    let location_id_column = column("location_id").ok_or("missing location_id column")?;
+    let type_id_column = column("type_id").ok_or("missing type_id column")?;
+    let price_columns = [column("sell_price"), column("buy_price"), column("best_bid"), column("best_ask")];
+
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+            let line_number = i + 2;
+
+            let location = cells
+                .get(location_type_column)
+                .zip(cells.get(location_id_column).and_then(|cell| cell.parse().ok()))
+                .and_then(|(kind, id)| MarketLocation::from_parts(kind, id))
+                .ok_or_else(|| format!("line {}: invalid location", line_number))?;
+            let type_id = cells
+                .get(type_id_column)
+                .and_then(|cell| cell.parse().ok())
+                .ok_or_else(|| format!("line {}: invalid type_id", line_number))?;
+
+            let mut values = [None; 4];
+            for (value, column) in values.iter_mut().zip(price_columns) {
+                let Some(cell) = column.and_then(|c| cells.get(c)).filter(|cell| !cell.is_empty()) else {
+                    continue;
+                };
+                *value = Some(cell.parse::<f64>().map_err(|_| format!("line {}: invalid price '{}'", line_number, cell))?);
+            }
+
+            let [sell_price, buy_price, best_bid, best_ask] = values;
+            Ok(FilePrice {
+                location,
+                type_id,
+                price: SourcePrice { sell_price, buy_price, best_bid, best_ask },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `contents` to a file in the temp directory, returning its path
+    fn price_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("salvo-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_static_file_source_reads_csv_and_json() {
+        let csv = price_file(
+            "prices.csv",
+            "location_type,location_id,type_id,sell_price,buy_price,best_ask\n\
+             region,10000002,34,5.5,5.0,5.4\n\
+             region,10000002,35,,9.0,\n\
+             station,60003760,34,5.9,5.1,5.8\n",
+        );
+        let json = price_file(
+            "prices.json",
+            r#"[{"location": {"region": 10000002}, "type_id": 34, "sell_price": 5.5, "buy_price": 5.0, "best_ask": 5.4},
+                {"location": {"station": 60003760}, "type_id": 34, "sell_price": 5.9, "buy_price": 5.1}]"#,
+        );
+
+        for path in [&csv, &json] {
+            let source = StaticFileSource::new(path);
+            let prices = source.fetch_prices(MarketLocation::THE_FORGE, &[34]).await.unwrap().prices;
+
+            assert_eq!(prices.len(), 1);
+            assert_eq!(
+                prices[&34],
+                SourcePrice { sell_price: Some(5.5), buy_price: Some(5.0), best_bid: None, best_ask: Some(5.4) }
+            );
+
+            // Each location gets its own rows, and locations missing from the file get none
+            let jita = source.fetch_prices(MarketLocation::Station(60003760), &[34]).await.unwrap().prices;
+            assert_eq!(jita[&34].sell_price, Some(5.9));
+            let domain = source.fetch_prices(MarketLocation::Region(10000043), &[34]).await.unwrap().prices;
+            assert!(domain.is_empty());
+        }
+
+        // Every type in the file; empty cells are missing prices
+        let prices = StaticFileSource::new(&csv).fetch_prices(MarketLocation::THE_FORGE, &[]).await.unwrap().prices;
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[&35].sell_price, None);
+        assert_eq!(prices[&35].buy_price, Some(9.0));
+
+        std::fs::remove_file(csv).unwrap();
+        std::fs::remove_file(json).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_global_location_is_priced_only_by_global_sources() {
+        let sources = PriceSources::new(
+            crate::test_support::pool().await,
+            Arc::new(EsiClient::new()),
+            Arc::new(FuzzworksClient::new()),
+            PriceSourceKind::File,
+            Some(PathBuf::from("prices.json")),
+        );
+        let global: MarketLocation = serde_json::from_str(r#""global""#).unwrap();
+        assert_eq!(global, MarketLocation::Global);
+
+        let average = sources.for_location(MarketLocation::Global).unwrap();
+        let file = sources.for_location(MarketLocation::THE_FORGE).unwrap();
+        assert_eq!(average.name(), "esi_average");
+        assert_eq!(file.name(), "file");
+
+        assert!(check_location(average.as_ref(), MarketLocation::Global).is_ok());
+        assert!(check_location(average.as_ref(), MarketLocation::THE_FORGE).is_err());
+        assert!(check_location(file.as_ref(), MarketLocation::Station(60003760)).is_ok());
+        assert!(check_location(file.as_ref(), MarketLocation::Global).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_reports_bad_rows() {
+        let header = "location_type,location_id,type_id,sell_price\n";
+        assert_eq!(parse_csv(&format!("{}region,10000002,abc,1.0", header)).unwrap_err(), "line 2: invalid type_id");
+        assert_eq!(parse_csv(&format!("{}system,30000142,34,1.0", header)).unwrap_err(), "line 2: invalid location");
+        assert!(parse_csv("type_id,sell_price\n34,1.0").is_err());
+    }
+}
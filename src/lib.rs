@@ -4,3 +4,6 @@ pub mod db;
 pub mod error;
 pub mod external;
 pub mod services;
+
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
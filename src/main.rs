@@ -1,14 +1,14 @@
-mod api;
-mod db;
-mod error;
-mod external;
-mod services;
-
 use axum::{
     routing::get,
-    Router,
+    Extension, Router,
+};
+use salvo_backend::{
+    api, db,
+    external::{esi::EsiClient, fuzzworks::FuzzworksClient, http_cache::HttpCache, price_source::PriceSources},
+    services::price_refresh,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tower_http::{trace::TraceLayer, cors::CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -29,11 +29,31 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database
     let db_pool = db::init_pool().await?;
 
+    // One ESI and one Fuzzworks client for the request handlers and the background refresh,
+    // caching responses in the database
+    let http_cache = HttpCache::new(db_pool.clone());
+    let esi_client = Arc::new(EsiClient::new().with_cache(http_cache.clone()));
+    let fuzzworks_client = Arc::new(FuzzworksClient::new().with_cache(http_cache));
+    let price_sources = PriceSources::from_env(db_pool.clone(), esi_client, fuzzworks_client)?;
+    tracing::info!("Default price source: {:?}", price_sources.default_kind());
+
+    // Refresh cached market prices in the background
+    let refresh_config = price_refresh::RefreshConfig::from_env();
+    match refresh_config {
+        Some(config) => {
+            tracing::info!("Refreshing market prices every {} minutes", config.interval.as_secs() / 60);
+            price_refresh::spawn(db_pool.clone(), config, price_sources.clone());
+        }
+        None => tracing::info!("Background price refresh disabled"),
+    }
+
     // Build application routes
     let app = Router::new()
         .route("/", get(root))
         .route("/health", get(health_check))
         .nest("/api", api::routes())
+        .layer(Extension(price_sources))
+        .layer(Extension(refresh_config))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
         .with_state(db_pool);
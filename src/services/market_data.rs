@@ -1,13 +1,45 @@
-use crate::db::{models::*, queries, DbPool};
+use crate::db::{models::*, parse_timestamp, queries, DbPool};
 use crate::error::{ApiError, ApiResult};
+use crate::external::price_source::SourcePrice;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::collections::HashMap;
 
-/// Get market prices for type IDs (from database cache)
+/// How much a cached price can be trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceStatus {
+    /// Updated within the maximum price age
+    Fresh,
+    /// Older than the maximum price age
+    Stale,
+    /// No cached sell price; it counts as 0
+    Missing,
+}
+
+/// Classify a cached price by its sell price and age
+pub fn price_status(price: Option<&MarketPrice>, max_age_minutes: i64, now: DateTime<Utc>) -> PriceStatus {
+    match price {
+        Some(price) if price.sell_price.is_some() => match price_age_minutes(price, now) {
+            Some(age) if age <= max_age_minutes => PriceStatus::Fresh,
+            _ => PriceStatus::Stale,
+        },
+        _ => PriceStatus::Missing,
+    }
+}
+
+/// Minutes since a price was cached; None if its timestamp can't be read
+pub fn price_age_minutes(price: &MarketPrice, now: DateTime<Utc>) -> Option<i64> {
+    parse_timestamp(&price.updated_at).map(|updated_at| (now - updated_at).num_minutes().max(0))
+}
+
+/// Get market prices for type IDs at a location (from database cache)
 pub async fn get_cached_prices(
     pool: &DbPool,
     type_ids: &[i32],
+    location: MarketLocation,
 ) -> ApiResult<HashMap<i32, MarketPrice>> {
-    let prices = queries::get_market_prices(pool, type_ids)
+    let prices = queries::get_market_prices(pool, type_ids, location)
         .await
         .map_err(ApiError::Database)?;
 
@@ -17,13 +49,61 @@ pub async fn get_cached_prices(
     Ok(price_map)
 }
 
-/// Calculate material costs from cached market prices
+/// Cache fetched prices at a location (and append them to the price history)
+///
+/// Only `type_ids` are stored, or every fetched type if it is empty. Types missing from
+/// eve_types are skipped. Returns the stored prices.
+pub async fn store_prices(
+    pool: &DbPool,
+    location: MarketLocation,
+    prices: &HashMap<i32, SourcePrice>,
+    type_ids: &[i32],
+) -> ApiResult<Vec<MarketPrice>> {
+    let wanted: Vec<i32> = if type_ids.is_empty() {
+        prices.keys().copied().collect()
+    } else {
+        type_ids.to_vec()
+    };
+
+    // Prices for unknown types can't be stored (foreign key)
+    let known_types = queries::get_existing_type_ids(pool, &wanted)
+        .await
+        .map_err(ApiError::Database)?;
+
+    let market_prices: Vec<MarketPrice> = wanted
+        .iter()
+        .filter(|type_id| known_types.contains(type_id))
+        .filter_map(|type_id| {
+            let price = prices.get(type_id)?;
+            Some(MarketPrice {
+                type_id: *type_id,
+                location_type: location.kind().to_string(),
+                location_id: location.id(),
+                sell_price: price.sell_price,
+                buy_price: price.buy_price,
+                daily_volume: None, // No source reports traded volume
+                updated_at: String::new(),
+                best_bid: price.best_bid,
+                best_ask: price.best_ask,
+            })
+        })
+        .collect();
+
+    queries::upsert_market_prices(pool, &market_prices)
+        .await
+        .map_err(ApiError::Database)?;
+
+    Ok(market_prices)
+}
+
+/// Calculate material costs from cached market prices at a location
 pub async fn calculate_material_costs(
     pool: &DbPool,
     materials: &[Material],
+    location: MarketLocation,
 ) -> ApiResult<f64> {
     let type_ids: Vec<i32> = materials.iter().map(|m| m.type_id).collect();
-    let prices = get_cached_prices(pool, &type_ids).await?;
+    let prices = get_cached_prices(pool, &type_ids, location).await?;
 
     let total_cost: f64 = materials
         .iter()
@@ -56,3 +136,68 @@ pub fn calculate_profit_margin(profit: f64, product_sell_price: f64) -> f64 {
         (profit / product_sell_price) * 100.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    async fn test_cached_prices_are_kept_per_location() {
+        let pool = test_support::pool().await;
+
+        let jita = MarketLocation::Station(60003760);
+        let amarr = MarketLocation::Station(60008494);
+        let prices: Vec<MarketPrice> = [(jita, 5.0), (amarr, 6.5), (MarketLocation::Global, 4.75)]
+            .into_iter()
+            .map(|(location, sell_price)| MarketPrice {
+                type_id: 34,
+                location_type: location.kind().to_string(),
+                location_id: location.id(),
+                sell_price: Some(sell_price),
+                buy_price: None,
+                daily_volume: None,
+                updated_at: String::new(),
+                best_bid: None,
+                best_ask: None,
+            })
+            .collect();
+        queries::upsert_market_prices(&pool, &prices).await.unwrap();
+
+        let at = |location| get_cached_prices(&pool, &[34], location);
+        assert_eq!(at(jita).await.unwrap()[&34].sell_price, Some(5.0));
+        assert_eq!(at(amarr).await.unwrap()[&34].sell_price, Some(6.5));
+        assert_eq!(at(MarketLocation::Global).await.unwrap()[&34].sell_price, Some(4.75));
+        // Seeded prices were migrated to The Forge region
+        assert_eq!(at(MarketLocation::THE_FORGE).await.unwrap()[&34].sell_price, Some(5.5));
+
+        // Each stored price is also appended to that location's history
+        let history = queries::get_price_history(&pool, 34, jita, 1).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sell_price, Some(5.0));
+    }
+
+    #[test]
+    fn test_price_status_by_age() {
+        let now = parse_timestamp("2024-11-11 12:00:00").unwrap();
+        let price = |sell_price, updated_at: &str| MarketPrice {
+            type_id: 34,
+            location_type: "region".to_string(),
+            location_id: 10000002,
+            sell_price,
+            buy_price: None,
+            daily_volume: None,
+            updated_at: updated_at.to_string(),
+            best_bid: None,
+            best_ask: None,
+        };
+
+        let recent = price(Some(5.0), "2024-11-11 11:30:00");
+        assert_eq!(price_age_minutes(&recent, now), Some(30));
+        assert_eq!(price_status(Some(&recent), 60, now), PriceStatus::Fresh);
+        assert_eq!(price_status(Some(&recent), 10, now), PriceStatus::Stale);
+
+        assert_eq!(price_status(Some(&price(None, "2024-11-11 11:30:00")), 60, now), PriceStatus::Missing);
+        assert_eq!(price_status(None, 60, now), PriceStatus::Missing);
+    }
+}
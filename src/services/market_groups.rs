@@ -0,0 +1,121 @@
+use crate::db::{models::*, queries, DbPool};
+use crate::error::{ApiError, ApiResult};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Market group with its child groups (one node of the market browser tree)
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketGroupNode {
+    pub market_group_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub has_types: bool,
+    pub children: Vec<MarketGroupNode>,
+}
+
+/// Load the market group tree, either from the top level or below a given group
+pub async fn get_market_group_tree(
+    pool: &DbPool,
+    root_group_id: Option<i32>,
+) -> ApiResult<Vec<MarketGroupNode>> {
+    let groups = queries::get_market_groups(pool)
+        .await
+        .map_err(ApiError::Database)?;
+
+    if let Some(root_id) = root_group_id {
+        if !groups.iter().any(|g| g.market_group_id == root_id) {
+            return Err(ApiError::NotFound(format!("Market group {} not found", root_id)));
+        }
+    }
+
+    Ok(build_tree(groups, root_group_id))
+}
+
+/// Arrange flat market groups into a tree below `root_group_id` (None = top level)
+///
+/// Groups whose parent is missing from the data are treated as top-level groups.
+pub fn build_tree(groups: Vec<MarketGroup>, root_group_id: Option<i32>) -> Vec<MarketGroupNode> {
+    let known: HashSet<i32> =
+        groups.iter().map(|g| g.market_group_id).collect();
+
+    let mut children_by_parent: HashMap<Option<i32>, Vec<MarketGroup>> = HashMap::new();
+    for group in groups {
+        let parent = group.parent_group_id.filter(|p| known.contains(p));
+        children_by_parent.entry(parent).or_default().push(group);
+    }
+
+    attach_children(&mut children_by_parent, root_group_id)
+}
+
+fn attach_children(
+    children_by_parent: &mut HashMap<Option<i32>, Vec<MarketGroup>>,
+    parent: Option<i32>,
+) -> Vec<MarketGroupNode> {
+    let mut children = children_by_parent.remove(&parent).unwrap_or_default();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+
+    children
+        .into_iter()
+        .map(|group| MarketGroupNode {
+            children: attach_children(children_by_parent, Some(group.market_group_id)),
+            market_group_id: group.market_group_id,
+            name: group.name,
+            description: group.description,
+            has_types: group.has_types,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(id: i32, parent: Option<i32>, name: &str) -> MarketGroup {
+        MarketGroup {
+            market_group_id: id,
+            parent_group_id: parent,
+            name: name.to_string(),
+            description: None,
+            has_types: false,
+        }
+    }
+
+    #[test]
+    fn test_build_tree_nests_children() {
+        let groups = vec![
+            group(9, None, "Ship Equipment"),
+            group(1111, Some(9), "Rigs"),
+            group(1112, Some(1111), "Armor Rigs"),
+            group(4, None, "Ships"),
+        ];
+
+        let tree = build_tree(groups, None);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].name, "Ship Equipment");
+        assert_eq!(tree[0].children[0].name, "Rigs");
+        assert_eq!(tree[0].children[0].children[0].market_group_id, 1112);
+        assert!(tree[1].children.is_empty());
+    }
+
+    #[test]
+    fn test_build_tree_from_subtree_root() {
+        let groups = vec![
+            group(9, None, "Ship Equipment"),
+            group(1111, Some(9), "Rigs"),
+            group(1112, Some(1111), "Armor Rigs"),
+        ];
+
+        let tree = build_tree(groups, Some(1111));
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].market_group_id, 1112);
+    }
+
+    #[test]
+    fn test_build_tree_orphans_become_roots() {
+        let groups = vec![group(5, Some(999), "Orphan")];
+
+        let tree = build_tree(groups, None);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].market_group_id, 5);
+    }
+}
@@ -2,11 +2,25 @@ use crate::db::{models::*, queries, DbPool};
 use crate::error::{ApiError, ApiResult};
 use std::collections::HashMap;
 
+/// Type ID of the Reprocessing skill
+pub const REPROCESSING_SKILL_ID: i32 = 3385;
+/// Type ID of the Reprocessing Efficiency skill
+pub const REPROCESSING_EFFICIENCY_SKILL_ID: i32 = 3389;
+
 /// Calculate materials from reprocessed salvage
 pub async fn calculate_materials(
     pool: &DbPool,
     salvage_items: &[SalvageInput],
     reprocessing_efficiency: f64,
+) -> ApiResult<Vec<Material>> {
+    calculate_materials_with(pool, salvage_items, |_| reprocessing_efficiency).await
+}
+
+/// Calculate materials from reprocessed salvage, with a reprocessing efficiency per item
+pub async fn calculate_materials_with(
+    pool: &DbPool,
+    salvage_items: &[SalvageInput],
+    efficiency_for: impl Fn(&SalvageInput) -> f64,
 ) -> ApiResult<Vec<Material>> {
     let mut material_totals: HashMap<i32, i32> = HashMap::new();
 
@@ -15,6 +29,7 @@ pub async fn calculate_materials(
         let yields = queries::get_reprocessing_yields(pool, salvage.type_id)
             .await
             .map_err(ApiError::Database)?;
+        let reprocessing_efficiency = efficiency_for(salvage);
 
         // Apply reprocessing efficiency and salvage quantity
         for yield_entry in yields {
@@ -1,3 +1,7 @@
 pub mod blueprint_matcher;
 pub mod market_data;
+pub mod market_groups;
 pub mod material_calculator;
+pub mod order_book;
+pub mod price_history;
+pub mod price_refresh;
@@ -0,0 +1,209 @@
+use crate::db::{models::MarketLocation, queries, DbPool};
+use crate::error::{ApiError, ApiResult};
+use crate::external::esi::{EsiClient, MarketOrder};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Share of each side's volume averaged into the percentile price (5%, as Fuzzworks does)
+pub const PERCENTILE: f64 = 0.05;
+
+/// Up to this many types are fetched one type at a time; more fetch the whole region
+const PER_TYPE_FETCH_LIMIT: usize = 10;
+
+/// Jita IV - Moon 4 - Caldari Navy Assembly Plant
+pub const JITA_STATION_ID: i64 = 60003760;
+
+/// Prices for one type computed from an order book
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBookSummary {
+    /// Highest buy order
+    pub best_bid: Option<f64>,
+    /// Lowest sell order
+    pub best_ask: Option<f64>,
+    /// Volume-weighted average of the highest 5% of buy volume
+    pub buy_percentile: Option<f64>,
+    /// Volume-weighted average of the lowest 5% of sell volume
+    pub sell_percentile: Option<f64>,
+    pub buy_volume: i64,
+    pub sell_volume: i64,
+    pub buy_orders: usize,
+    pub sell_orders: usize,
+}
+
+/// Per-type summaries of an order book
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    pub summaries: HashMap<i32, OrderBookSummary>,
+    /// When ESI will next refresh the orders (from its `Expires` header)
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// Fetch the order book at a region or station; a station's book is read from its region
+///
+/// Fails with NotFound for stations missing from the universe tables, whose region is unknown.
+pub async fn fetch_location_order_book(
+    pool: &DbPool,
+    client: &EsiClient,
+    location: MarketLocation,
+    type_ids: &[i32],
+) -> ApiResult<OrderBook> {
+    let (region_id, station_id) = match location {
+        MarketLocation::Region(region_id) => (region_id, None),
+        MarketLocation::Station(station_id) => {
+            let region_id = queries::get_station_region(pool, station_id)
+                .await
+                .map_err(ApiError::Database)?
+                .ok_or_else(|| ApiError::NotFound(format!("Station {} not found", station_id)))?;
+            (region_id, Some(station_id))
+        }
+        MarketLocation::Global => {
+            return Err(ApiError::InvalidInput("ESI order books are per region; there is no global order book".to_string()));
+        }
+    };
+
+    fetch_order_book(client, region_id, station_id, type_ids).await
+}
+
+/// Fetch a region's order book from ESI and summarize it per type
+///
+/// With `station_id`, only orders at that station count (e.g. Jita 4-4).
+/// An empty `type_ids` summarizes every type with orders.
+pub async fn fetch_order_book(
+    client: &EsiClient,
+    region_id: i32,
+    station_id: Option<i64>,
+    type_ids: &[i32],
+) -> ApiResult<OrderBook> {
+    let (orders, expires) = if !type_ids.is_empty() && type_ids.len() <= PER_TYPE_FETCH_LIMIT {
+        let mut orders = Vec::new();
+        let mut expires = None;
+        for type_id in type_ids {
+            let book = client.get_region_orders(region_id, Some(*type_id)).await?;
+            orders.extend(book.orders);
+            expires = expires.max(book.expires);
+        }
+        (orders, expires)
+    } else {
+        let book = client.get_region_orders(region_id, None).await?;
+        (book.orders, book.expires)
+    };
+
+    tracing::info!("Fetched {} orders in region {}", orders.len(), region_id);
+
+    let mut summaries = summarize_orders(&orders, station_id);
+    if !type_ids.is_empty() {
+        summaries.retain(|type_id, _| type_ids.contains(type_id));
+    }
+
+    Ok(OrderBook { summaries, expires })
+}
+
+/// (price, volume) of one order
+type PriceLevel = (f64, i64);
+
+/// Summarize orders per type, optionally only those at one station
+pub fn summarize_orders(orders: &[MarketOrder], station_id: Option<i64>) -> HashMap<i32, OrderBookSummary> {
+    // (buys, sells) per type
+    let mut books: HashMap<i32, (Vec<PriceLevel>, Vec<PriceLevel>)> = HashMap::new();
+
+    for order in orders {
+        if station_id.is_some_and(|station_id| order.location_id != station_id) {
+            continue;
+        }
+
+        let (buys, sells) = books.entry(order.type_id).or_default();
+        let side = if order.is_buy_order { buys } else { sells };
+        side.push((order.price, order.volume_remain as i64));
+    }
+
+    books
+        .into_iter()
+        .map(|(type_id, (mut buys, mut sells))| {
+            // Best orders first: highest buys, lowest sells
+            buys.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            sells.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            let summary = OrderBookSummary {
+                best_bid: buys.first().map(|(price, _)| *price),
+                best_ask: sells.first().map(|(price, _)| *price),
+                buy_percentile: percentile_price(&buys),
+                sell_percentile: percentile_price(&sells),
+                buy_volume: buys.iter().map(|(_, volume)| volume).sum(),
+                sell_volume: sells.iter().map(|(_, volume)| volume).sum(),
+                buy_orders: buys.len(),
+                sell_orders: sells.len(),
+            };
+            (type_id, summary)
+        })
+        .collect()
+}
+
+/// Volume-weighted average price of the best PERCENTILE of volume (orders sorted best first)
+fn percentile_price(orders: &[PriceLevel]) -> Option<f64> {
+    let total_volume: i64 = orders.iter().map(|(_, volume)| volume).sum();
+    if total_volume <= 0 {
+        return None;
+    }
+
+    let target = ((total_volume as f64 * PERCENTILE).ceil() as i64).max(1);
+    let mut remaining = target;
+    let mut value = 0.0;
+
+    for (price, volume) in orders {
+        let taken = remaining.min(*volume);
+        value += price * taken as f64;
+        remaining -= taken;
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    Some(value / target as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(type_id: i32, location_id: i64, price: f64, volume_remain: i32, is_buy_order: bool) -> MarketOrder {
+        MarketOrder {
+            order_id: 0,
+            type_id,
+            location_id,
+            price,
+            volume_remain,
+            is_buy_order,
+        }
+    }
+
+    #[test]
+    fn test_summarize_orders_at_station() {
+        let orders = vec![
+            order(34, JITA_STATION_ID, 5.0, 50, false),
+            order(34, JITA_STATION_ID, 6.0, 950, false),
+            order(34, JITA_STATION_ID, 4.0, 100, true),
+            order(34, JITA_STATION_ID, 3.0, 900, true),
+            // Cheaper, but not at the hub
+            order(34, 60008494, 1.0, 1000, false),
+        ];
+
+        let summaries = summarize_orders(&orders, Some(JITA_STATION_ID));
+        let tritanium = &summaries[&34];
+
+        assert_eq!(tritanium.best_ask, Some(5.0));
+        assert_eq!(tritanium.best_bid, Some(4.0));
+        assert_eq!(tritanium.sell_volume, 1000);
+        assert_eq!(tritanium.sell_orders, 2);
+        // 5% of 1000 units: all 50 units come from the best sell order
+        assert_eq!(tritanium.sell_percentile, Some(5.0));
+        assert_eq!(tritanium.buy_percentile, Some(4.0));
+    }
+
+    #[test]
+    fn test_percentile_spans_orders() {
+        // 5% of 200 units = 10: 4 at 10.0 and 6 at 20.0
+        let orders = vec![(10.0, 4), (20.0, 196)];
+        assert_eq!(percentile_price(&orders), Some(16.0));
+        assert_eq!(percentile_price(&[]), None);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::db::models::DailyPrice;
+use serde::Serialize;
+
+/// A day's average prices with their trailing moving averages
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MovingAveragePoint {
+    pub day: String,
+    pub sell_price: Option<f64>,
+    pub buy_price: Option<f64>,
+    /// Average sell price over this day and the `window - 1` days before it
+    pub sell_average: Option<f64>,
+    pub buy_average: Option<f64>,
+}
+
+/// Spread of a price series
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceStats {
+    pub samples: usize,
+    pub latest: f64,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub stddev: f64,
+    /// stddev relative to the mean, in percent
+    pub coefficient_of_variation: f64,
+    /// Standard deviation of day-to-day price changes, in percent
+    pub daily_change_stddev: f64,
+    /// Latest price relative to the mean, in percent (large for a spike, small for a steady trend)
+    pub latest_vs_mean: f64,
+}
+
+/// Trailing moving averages over `window` days of daily prices (oldest first)
+///
+/// Days without a price are skipped when averaging.
+pub fn moving_averages(days: &[DailyPrice], window: usize) -> Vec<MovingAveragePoint> {
+    let window = window.max(1);
+
+    days.iter()
+        .enumerate()
+        .map(|(i, day)| {
+            let trailing = &days[(i + 1).saturating_sub(window)..=i];
+            MovingAveragePoint {
+                day: day.day.clone(),
+                sell_price: day.sell_price,
+                buy_price: day.buy_price,
+                sell_average: mean(trailing.iter().filter_map(|d| d.sell_price)),
+                buy_average: mean(trailing.iter().filter_map(|d| d.buy_price)),
+            }
+        })
+        .collect()
+}
+
+/// Statistics of a price series (oldest first); None when it is empty
+pub fn price_stats(prices: &[f64]) -> Option<PriceStats> {
+    let latest = *prices.last()?;
+    let price_mean = mean(prices.iter().copied())?;
+
+    let price_stddev = stddev(prices, price_mean);
+    let changes: Vec<f64> = prices
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| (pair[1] - pair[0]) / pair[0] * 100.0)
+        .collect();
+
+    let change_stddev = mean(changes.iter().copied()).map_or(0.0, |m| stddev(&changes, m));
+    let percent_of_mean = |value: f64| if price_mean == 0.0 { 0.0 } else { value / price_mean * 100.0 };
+
+    Some(PriceStats {
+        samples: prices.len(),
+        latest,
+        mean: price_mean,
+        min: prices.iter().copied().fold(f64::INFINITY, f64::min),
+        max: prices.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        stddev: price_stddev,
+        coefficient_of_variation: percent_of_mean(price_stddev),
+        daily_change_stddev: change_stddev,
+        latest_vs_mean: percent_of_mean(latest - price_mean),
+    })
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Population standard deviation
+fn stddev(values: &[f64], mean: f64) -> f64 {
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: &str, sell_price: Option<f64>) -> DailyPrice {
+        DailyPrice {
+            day: day.to_string(),
+            sell_price,
+            buy_price: None,
+            samples: 1,
+        }
+    }
+
+    #[test]
+    fn test_moving_averages_trail_the_window() {
+        let days = vec![
+            day("2024-11-01", Some(10.0)),
+            day("2024-11-02", Some(20.0)),
+            day("2024-11-03", None),
+            day("2024-11-04", Some(60.0)),
+        ];
+
+        let points = moving_averages(&days, 2);
+
+        let averages: Vec<Option<f64>> = points.iter().map(|p| p.sell_average).collect();
+        assert_eq!(averages, vec![Some(10.0), Some(15.0), Some(20.0), Some(60.0)]);
+        assert_eq!(points[0].buy_average, None);
+    }
+
+    #[test]
+    fn test_price_stats_flags_a_spike() {
+        let stats = price_stats(&[100.0, 100.0, 100.0, 200.0]).unwrap();
+
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.mean, 125.0);
+        assert_eq!(stats.min, 100.0);
+        assert_eq!(stats.max, 200.0);
+        assert!((stats.stddev - 43.30).abs() < 0.01);
+        // Latest price is 60% above the mean
+        assert_eq!(stats.latest_vs_mean, 60.0);
+        assert!(price_stats(&[]).is_none());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::db::{
+    format_timestamp,
+    models::{MarketLocation, PriceRefreshRun},
+    parse_timestamp, queries, DbPool,
+};
+use crate::error::{ApiError, ApiResult};
+use crate::external::price_source::{self, PriceSource, PriceSources};
+use crate::services::market_data;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Refresh interval unless PRICE_REFRESH_INTERVAL_MINUTES is set
+const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+
+/// Background price refresh settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshConfig {
+    /// Time between refreshes; a refresh is never run before the source's prices expire
+    pub interval: Duration,
+}
+
+impl RefreshConfig {
+    /// Settings from `PRICE_REFRESH_INTERVAL_MINUTES` (default 60); None when it is 0 (disabled)
+    pub fn from_env() -> Option<Self> {
+        let minutes = std::env::var("PRICE_REFRESH_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_INTERVAL_MINUTES);
+
+        (minutes > 0).then(|| Self {
+            interval: Duration::from_secs(minutes * 60),
+        })
+    }
+}
+
+/// What one refresh run stored
+#[derive(Debug, Default)]
+struct RefreshOutcome {
+    locations: i64,
+    updated_count: i64,
+    expires: Option<DateTime<Utc>>,
+}
+
+/// Start refreshing cached prices in the background from the default price source
+/// (ESI averages for global prices)
+///
+/// The first refresh runs immediately unless the last recorded run scheduled a later one.
+pub fn spawn(pool: DbPool, config: RefreshConfig, sources: PriceSources) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        match queries::fail_unfinished_price_refresh_runs(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::warn!("Marked {} interrupted price refresh runs as failed", count),
+            Err(e) => tracing::warn!("Could not check for interrupted price refresh runs: {}", e),
+        }
+
+        if let Err(e) = sources.default_source() {
+            tracing::error!("Price refresh disabled: {}", e);
+            return;
+        }
+
+        loop {
+            let next_run_at = match queries::get_last_price_refresh_run(&pool, None).await {
+                Ok(run) => run.and_then(|run| run.next_run_at).and_then(|at| parse_timestamp(&at)),
+                Err(e) => {
+                    tracing::warn!("Could not read the last price refresh run: {}", e);
+                    Some(Utc::now() + config.interval)
+                }
+            };
+
+            let delay = delay_until(next_run_at, Utc::now());
+            if !delay.is_zero() {
+                tracing::info!("Next price refresh in {}s", delay.as_secs());
+                tokio::time::sleep(delay).await;
+            }
+
+            if let Err(e) = run_refresh(&pool, &sources, &config).await {
+                // The run may not have been recorded (e.g. the database is locked), leaving
+                // no future next_run_at; wait a full interval rather than retrying at once
+                tracing::error!("Price refresh failed, retrying in {}s: {}", config.interval.as_secs(), e);
+                tokio::time::sleep(config.interval).await;
+            }
+        }
+    })
+}
+
+/// Refresh every cached price once, recording the run in price_refresh_runs
+pub async fn run_refresh(
+    pool: &DbPool,
+    sources: &PriceSources,
+    config: &RefreshConfig,
+) -> ApiResult<PriceRefreshRun> {
+    let run_id = queries::start_price_refresh_run(pool)
+        .await
+        .map_err(ApiError::Database)?;
+
+    tracing::info!("Price refresh {} started ({:?})", run_id, sources.default_kind());
+    let result = refresh_prices(pool, sources).await;
+    let now = Utc::now();
+
+    let run = match &result {
+        Ok(outcome) => PriceRefreshRun {
+            run_id,
+            started_at: String::new(),
+            finished_at: None,
+            status: "success".to_string(),
+            locations: outcome.locations,
+            updated_count: outcome.updated_count,
+            expires_at: outcome.expires.map(format_timestamp),
+            next_run_at: Some(format_timestamp(next_run_at(now, config.interval, outcome.expires))),
+            error: None,
+        },
+        Err(e) => PriceRefreshRun {
+            run_id,
+            started_at: String::new(),
+            finished_at: None,
+            status: "failed".to_string(),
+            locations: 0,
+            updated_count: 0,
+            expires_at: None,
+            next_run_at: Some(format_timestamp(now + config.interval)),
+            error: Some(e.to_string()),
+        },
+    };
+
+    queries::finish_price_refresh_run(pool, &run)
+        .await
+        .map_err(ApiError::Database)?;
+
+    let outcome = result?;
+    tracing::info!(
+        "Price refresh {} updated {} prices at {} locations",
+        run_id,
+        outcome.updated_count,
+        outcome.locations
+    );
+
+    Ok(run)
+}
+
+/// Re-price every cached (type, location) from the source for its location
+async fn refresh_prices(pool: &DbPool, sources: &PriceSources) -> ApiResult<RefreshOutcome> {
+    let mut cached: BTreeMap<(String, i64), Vec<i32>> = BTreeMap::new();
+    for (location_type, location_id, type_id) in queries::get_cached_price_keys(pool)
+        .await
+        .map_err(ApiError::Database)?
+    {
+        cached.entry((location_type, location_id)).or_default().push(type_id);
+    }
+
+    let mut outcome = RefreshOutcome::default();
+
+    for ((location_type, location_id), type_ids) in cached {
+        let Some(location) = MarketLocation::from_parts(&location_type, location_id) else {
+            continue;
+        };
+
+        let source = sources.for_location(location)?;
+        let refreshed = match refresh_location(pool, source.as_ref(), location, &type_ids).await {
+            Ok(refreshed) => refreshed,
+            Err(ApiError::NotFound(message)) => {
+                tracing::warn!("Skipping prices at {}: {}", location, message);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        outcome.locations += 1;
+        outcome.updated_count += refreshed.updated_count as i64;
+        outcome.expires = outcome.expires.max(refreshed.expires);
+    }
+
+    Ok(outcome)
+}
+
+/// Prices re-fetched at one location
+#[derive(Debug, Default)]
+pub struct LocationRefresh {
+    pub updated_count: usize,
+    /// When the source will next have newer prices
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// Re-price types at a location from `source`
+///
+/// Fails with InvalidInput if `source` doesn't price `location` (see
+/// `price_source::check_location`), and with NotFound for stations the source can't locate
+/// (e.g. ESI order books for stations missing from the universe tables).
+pub async fn refresh_location(
+    pool: &DbPool,
+    source: &dyn PriceSource,
+    location: MarketLocation,
+    type_ids: &[i32],
+) -> ApiResult<LocationRefresh> {
+    price_source::check_location(source, location)?;
+    let fetched = source.fetch_prices(location, type_ids).await?;
+
+    let stored = market_data::store_prices(pool, location, &fetched.prices, type_ids).await?;
+
+    Ok(LocationRefresh {
+        updated_count: stored.len(),
+        expires: fetched.expires,
+    })
+}
+
+/// Next refresh after `interval`, but not before the source's prices expire
+fn next_run_at(now: DateTime<Utc>, interval: Duration, expires: Option<DateTime<Utc>>) -> DateTime<Utc> {
+    let after_interval = now + interval;
+    expires.map_or(after_interval, |expires| after_interval.max(expires))
+}
+
+/// Time left until `at`; zero when it has passed or was never scheduled
+fn delay_until(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Duration {
+    at.and_then(|at| (at - now).to_std().ok()).unwrap_or(Duration::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external::{
+        esi::EsiClient,
+        fuzzworks::FuzzworksClient,
+        price_source::{PriceSourceKind, StaticFileSource},
+    };
+    use crate::test_support;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_refresh_reprices_cached_prices_from_source() {
+        let pool = test_support::pool().await;
+        let path = std::env::temp_dir().join(format!("salvo-{}-refresh.json", std::process::id()));
+        std::fs::write(&path, r#"[{"location": {"region": 10000002}, "type_id": 34, "sell_price": 7.25, "buy_price": 7.0}]"#).unwrap();
+
+        let sources = PriceSources::new(
+            pool.clone(),
+            Arc::new(EsiClient::new()),
+            Arc::new(FuzzworksClient::new()),
+            PriceSourceKind::File,
+            Some(path.clone()),
+        );
+        let config = RefreshConfig { interval: Duration::from_secs(60) };
+        let run = run_refresh(&pool, &sources, &config).await.unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        // Seeded prices are cached at The Forge; only those in the file are re-priced
+        assert_eq!(run.status, "success");
+        assert_eq!(run.updated_count, 1);
+        let prices = queries::get_market_prices(&pool, &[34], MarketLocation::THE_FORGE).await.unwrap();
+        assert_eq!(prices[0].sell_price, Some(7.25));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_location_rejects_global_for_a_local_source() {
+        let pool = test_support::pool().await;
+        let source = StaticFileSource::new(std::env::temp_dir().join("salvo-unused-prices.json"));
+
+        let error = refresh_location(&pool, &source, MarketLocation::Global, &[34]).await.unwrap_err();
+
+        assert!(matches!(error, ApiError::InvalidInput(_)));
+    }
+
+    #[test]
+    fn test_next_run_waits_for_cache_expiry() {
+        let now = parse_timestamp("2024-11-11 12:00:00").unwrap();
+        let interval = Duration::from_secs(60);
+
+        // Expiry before the interval is up: the interval wins
+        let soon = parse_timestamp("2024-11-11 12:00:30");
+        assert_eq!(format_timestamp(next_run_at(now, interval, soon)), "2024-11-11 12:01:00");
+
+        // ESI's cache outlives the interval: wait for it
+        let later = parse_timestamp("2024-11-11 12:05:00");
+        assert_eq!(format_timestamp(next_run_at(now, interval, later)), "2024-11-11 12:05:00");
+
+        assert_eq!(delay_until(later, now), Duration::from_secs(300));
+        assert_eq!(delay_until(Some(now), later.unwrap()), Duration::ZERO);
+        assert_eq!(delay_until(None, now), Duration::ZERO);
+    }
+}
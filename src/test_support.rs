@@ -0,0 +1,26 @@
+//! Fixtures shared by the library and import_sde tests
+
+use axum::Router;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+
+/// Fresh in-memory database with every migration applied
+///
+/// The pool has a single connection, so every query sees the same in-memory database.
+pub async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    pool
+}
+
+/// Serve `app` on a random local port, returning its base URL
+pub async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}", addr)
+}